@@ -5,42 +5,37 @@ pub mod dapps_staking_developer {
 
     use inkv5_client_lib::only_role;
     use inkv5_client_lib::traits::access_control::*;
+    use lucky::traits::timelock::*;
 
     pub const WHITELISTED_ADDRESS: RoleType = ink::selector_id!("WHITELISTED_ADDRESS");
 
-    /// Errors occurred in the contract
-    #[derive(Debug, Eq, PartialEq)]
-    #[ink::scale_derive(Encode, Decode, TypeInfo)]
-    #[allow(clippy::cast_possible_truncation)]
-    pub enum ContractError {
-        AccessControlError(AccessControlError),
-        TransferError,
-        UpgradeError,
-    }
+    /// Errors occurred in the contract, shared with the raffle consumer contract
+    /// so that they are kept when the withdrawal fails
+    pub use lucky::traits::error::DappsStakingDeveloperError as ContractError;
 
-    /// convertor from AccessControlError to ContractError
-    impl From<AccessControlError> for ContractError {
-        fn from(error: AccessControlError) -> Self {
-            ContractError::AccessControlError(error)
-        }
-    }
+    /// Operations executed after the timelock delay, there is no operation specific to this contract
+    pub type Operation = lucky::traits::timelock::Operation<()>;
 
     /// Contract storage
     #[derive(Default)]
     #[ink(storage)]
     pub struct Contract {
         access_control: AccessControlData,
+        timelock: TimelockData,
     }
 
     impl Contract {
+        /// The min delay is the number of blocks between the schedule and the execution of an operation
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new(min_delay: u32) -> Self {
             let mut instance = Self::default();
             let caller = instance.env().caller();
             // set the admin of this contract
             BaseAccessControl::init_with_admin(&mut instance, caller);
             BaseAccessControl::inner_grant_role(&mut instance, WHITELISTED_ADDRESS, caller)
                 .expect("Should grant the role WHITELISTED_ADDRESS");
+            BaseTimelock::inner_set_min_delay(&mut instance, min_delay)
+                .expect("Should set the min delay");
             instance
         }
 
@@ -59,19 +54,41 @@ pub mod dapps_staking_developer {
             Ok(())
         }
 
+        /// Schedule the operation, it can be executed by anyone after the min delay
+        #[ink(message)]
+        pub fn schedule_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            self.inner_schedule_operation(&operation)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            self.inner_cancel_operation(&operation)?;
+            Ok(())
+        }
+
+        /// Execute the operation when the min delay is over
+        #[ink(message)]
+        pub fn execute_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            self.inner_execute_operation(operation)?;
+            Ok(())
+        }
+
+        /// Deprecated: use the operation `UpgradeContract`.
+        /// The first call schedules the operation, the call after the min delay executes it
         #[ink(message)]
         pub fn upgrade_contract(&mut self, new_code_hash: Hash) -> Result<(), ContractError> {
-            only_role!(self, ADMIN_ROLE);
-            self.env()
-                .set_code_hash(&new_code_hash)
-                .map_err(|_| ContractError::UpgradeError)?;
+            self.inner_schedule_or_execute_operation(Operation::UpgradeContract(new_code_hash))?;
             Ok(())
         }
 
+        /// Deprecated: use the operation `TerminateContract`.
+        /// The first call schedules the operation, the call after the min delay executes it
         #[ink(message)]
         pub fn terminate_me(&mut self) -> Result<(), ContractError> {
-            only_role!(self, ADMIN_ROLE);
-            self.env().terminate_contract(self.env().caller());
+            let caller = self.env().caller();
+            self.inner_schedule_or_execute_operation(Operation::TerminateContract(caller))?;
+            Ok(())
         }
 
         #[ink(message)]
@@ -81,6 +98,31 @@ pub mod dapps_staking_developer {
     }
 
 
+    /// Boilerplate code to implement the timelock
+    impl TimelockStorage for Contract {
+        fn get_storage(&self) -> &TimelockData {
+            &self.timelock
+        }
+
+        fn get_mut_storage(&mut self) -> &mut TimelockData {
+            &mut self.timelock
+        }
+    }
+
+    impl BaseTimelock for Contract {}
+
+    impl Timelock for Contract {
+        #[ink(message)]
+        fn get_min_delay(&self) -> u32 {
+            self.inner_get_min_delay()
+        }
+
+        #[ink(message)]
+        fn get_operation_ready_at(&self, id: OperationId) -> Option<u32> {
+            self.inner_get_operation_ready_at(id)
+        }
+    }
+
     /// Boilerplate code to implement the access control
     impl AccessControlStorage for Contract {
         fn get_storage(&self) -> &AccessControlData {
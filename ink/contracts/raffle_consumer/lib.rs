@@ -4,7 +4,11 @@
 pub mod raffle_consumer {
     use ink::env::call::{ExecutionInput, Selector};
     use ink::env::debug_message;
-    use ink::prelude::vec::Vec;
+    use ink::env::hash::Blake2x256;
+    use ink::scale::{Decode, DecodeAll, Encode};
+    use ink::prelude::string::String;
+    use ink::prelude::{vec, vec::Vec};
+    use ink::storage::{Lazy, Mapping};
     use inkv5_client_lib::only_role;
     use inkv5_client_lib::traits::access_control::*;
     use inkv5_client_lib::traits::kv_store::*;
@@ -12,32 +16,255 @@ pub mod raffle_consumer {
     use inkv5_client_lib::traits::meta_transaction::*;
     use inkv5_client_lib::traits::rollup_client::*;
     use inkv5_client_lib::traits::*;
-    use lucky::traits::error::RaffleError;
+    use lucky::traits::error::{DappsStakingDeveloperError, RaffleError};
+    use lucky::traits::reward::{PSP22Error, RewardError};
+    use lucky::traits::pausable::*;
+    use lucky::traits::pool::*;
+    use lucky::traits::timelock::*;
+    use lucky::traits::winner_proof::{HashValue, WinnersProof};
 
     use lucky::traits::{participant_filter::filter_latest_winners, participant_filter::filter_latest_winners::*, raffle, raffle::*, RAFFLE_MANAGER_ROLE};
 
     // Selector of withdraw: "0x410fcc9d"
     const WITHDRAW_SELECTOR: [u8; 4] = [0x41, 0x0f, 0xcc, 0x9d];
-    // Selector of Psp22Reward::fund_rewards_and_add_winners": "0xc218e5ba"
-    const FUND_REWARDS_AND_WINNERS_SELECTOR: [u8; 4] = [0xc2, 0x18, 0xe5, 0xba];
+    // Selector of Psp22Reward::fund_rewards_by_era": "0xef51d0aa"
+    const FUND_REWARDS_BY_ERA_SELECTOR: [u8; 4] = [0xef, 0x51, 0xd0, 0xaa];
+    // Selector of PSP22::transfer: "0xdb20f9f5"
+    const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+    // Selector of PSP22::approve: "0xb20f1bbd"
+    const PSP22_APPROVE_SELECTOR: [u8; 4] = [0xb2, 0x0f, 0x1b, 0xbd];
+
+    /// Version of the storage layout.
+    /// When the layout changes, bump the version and add the migration step in `migrate`.
+    /// The new fields must be added at the end of the storage, or in a `Mapping`/`Lazy`,
+    /// so that the storage written by the previous code can still be read after the upgrade.
+    const STORAGE_VERSION: u16 = 1;
+
+    /// Number of blocks during which an attestor granted without evidence is accepted
+    /// (about one week with blocks of 12 seconds)
+    const DEFAULT_ATTESTOR_VALIDITY: BlockNumber = 50_400;
 
     /// Event emitted when the Raffle is done
     #[ink(event)]
     pub struct RaffleDone {
         #[ink(topic)]
         contract: AccountId,
+        pool_id: PoolId,
         #[ink(topic)]
         era: u32,
         pending_rewards: Balance,
         nb_winners: u16,
+        /// rewards not distributed by the ratios
+        remainder: Balance,
+        /// where the remainder goes, None when it is added in the jackpot
+        remainder_destination: Option<RemainderDestination>,
+        /// fee taken on the rewards of the winners and sent to the treasury
+        protocol_fee: Balance,
     }
 
     #[ink(event)]
     pub struct RaffleSkipped {
         #[ink(topic)]
         contract: AccountId,
+        pool_id: PoolId,
         #[ink(topic)]
         era: u32,
+        reason: SkipReason,
+    }
+
+    /// Event emitted when the PSP22 token used for the rewards is updated
+    #[ink(event)]
+    pub struct RewardTokenUpdated {
+        token: Option<AccountId>,
+    }
+
+    /// Event emitted for each winner of the Raffle
+    #[ink(event)]
+    pub struct WinnerDrawn {
+        pool_id: PoolId,
+        #[ink(topic)]
+        era: u32,
+        /// rank of the winner, starting from 1
+        rank: u16,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    /// Event emitted when the address of the dAppStaking developer contract is updated
+    #[ink(event)]
+    pub struct DappsStakingDeveloperAddressUpdated {
+        pool_id: PoolId,
+        address: AccountId,
+    }
+
+    /// Event emitted when a pool is removed
+    #[ink(event)]
+    pub struct PoolRemoved {
+        pool_id: PoolId,
+    }
+
+    /// Event emitted when the address of the reward manager contract is updated
+    #[ink(event)]
+    pub struct RewardManagerAddressUpdated {
+        address: AccountId,
+    }
+
+    /// Event emitted when the address of the treasury is updated
+    #[ink(event)]
+    pub struct TreasuryAddressUpdated {
+        address: AccountId,
+    }
+
+    /// Event emitted when the identifier of the chain is updated
+    #[ink(event)]
+    pub struct ChainIdUpdated {
+        chain_id: Hash,
+    }
+
+    /// Event emitted when the Raffle is done and the rewards wait for the end of the dispute window
+    #[ink(event)]
+    pub struct RafflePending {
+        #[ink(topic)]
+        contract: AccountId,
+        pool_id: PoolId,
+        #[ink(topic)]
+        era: u32,
+        release_block: BlockNumber,
+    }
+
+    /// Event emitted when the result of the Raffle is cancelled during the dispute window
+    #[ink(event)]
+    pub struct RaffleCancelled {
+        #[ink(topic)]
+        contract: AccountId,
+        pool_id: PoolId,
+        #[ink(topic)]
+        era: u32,
+        reason: String,
+    }
+
+    /// Event emitted when an attestor is registered with its attestation evidence
+    #[ink(event)]
+    pub struct AttestorRegistered {
+        #[ink(topic)]
+        attestor: AccountId,
+        quote_hash: Hash,
+        measurement: Hash,
+        expires_at: BlockNumber,
+    }
+
+    /// Event emitted when an attestor is revoked
+    #[ink(event)]
+    pub struct AttestorRevoked {
+        #[ink(topic)]
+        attestor: AccountId,
+    }
+
+    /// Event emitted when the storage is migrated to a new layout
+    #[ink(event)]
+    pub struct StorageMigrated {
+        from_version: u16,
+        to_version: u16,
+    }
+
+    /// Operations specific to this contract executed after the timelock delay
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum RaffleOperation {
+        /// transfer the value to the account
+        Withdraw(AccountId, Balance),
+        SetDappsStakingDeveloperAddress(AccountId),
+        SetRewardManagerAddress(AccountId),
+        /// set the PSP22 token used for the rewards, None to use the native currency.
+        /// It must be the token set in the reward manager
+        SetRewardToken(Option<AccountId>),
+        SetTreasuryAddress(AccountId),
+        /// set the identifier of the chain (ie the genesis hash) used in the domain separator
+        SetChainId(Hash),
+        /// set the address of the dAppStaking developer contract of the pool and register the pool
+        SetPoolDappsStakingDeveloperAddress(PoolId, AccountId),
+        /// unregister the pool, the default pool cannot be removed
+        RemovePool(PoolId),
+    }
+
+    /// Operations executed after the timelock delay
+    pub type Operation = lucky::traits::timelock::Operation<RaffleOperation>;
+
+    /// Result of a raffle waiting for the end of the dispute window
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PendingRaffle {
+        pub rewards: Balance,
+        /// winners in the order they have been drawn
+        pub winners: Vec<AccountId>,
+        pub winners_rewards: Vec<(AccountId, Balance)>,
+        /// jackpot before the raffle, restored if the raffle is cancelled
+        pub previous_jackpot: Balance,
+        /// block after which the raffle can be finalized
+        pub release_block: BlockNumber,
+        /// rewards not distributed by the ratios
+        pub remainder: Balance,
+        pub remainder_destination: Option<RemainderDestination>,
+        /// fee taken on the rewards of the winners
+        pub protocol_fee: Balance,
+        /// winners filter before the raffle, restored if the raffle is cancelled
+        pub winners_filter: WinnersFilterSnapshot,
+    }
+
+    /// Attestation evidence of an attestor running in a TEE
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct AttestorInfo {
+        /// hash of the attestation quote
+        pub quote_hash: Hash,
+        /// measurement of the code claimed in the quote
+        pub measurement: Hash,
+        pub registered_at: BlockNumber,
+        /// block from which the attestor is rejected
+        pub expires_at: BlockNumber,
+    }
+
+    /// Rewards of a raffle ready to be released
+    pub struct RaffleRelease {
+        era: u32,
+        rewards: Balance,
+        /// winners in the order they have been drawn
+        winners: Vec<AccountId>,
+        winners_rewards: Vec<(AccountId, Balance)>,
+        /// rewards not distributed by the ratios
+        remainder: Balance,
+        remainder_destination: Option<RemainderDestination>,
+        protocol_fee: Balance,
+    }
+
+    /// Event emitted when an attestor submits a response and the quorum is not reached yet
+    #[ink(event)]
+    pub struct ResponseSubmitted {
+        #[ink(topic)]
+        era: u32,
+        #[ink(topic)]
+        attestor: AccountId,
+        payload_hash: Hash,
+        nb_submissions: u8,
+    }
+
+    /// Event emitted when an attestor submits a response different from the ones already submitted
+    #[ink(event)]
+    pub struct ResponseDisagreement {
+        #[ink(topic)]
+        era: u32,
+        #[ink(topic)]
+        attestor: AccountId,
+        payload_hash: Hash,
+    }
+
+    /// Event emitted when the number of attestors who must submit the same response is updated
+    #[ink(event)]
+    pub struct QuorumThresholdUpdated {
+        quorum_threshold: u8,
     }
 
     /// Contract storage
@@ -52,225 +279,1543 @@ pub mod raffle_consumer {
         reward_manager_address: Option<AccountId>,
         raffle: raffle::RaffleData,
         filter_latest_winners: filter_latest_winners::FilterLatestWinnersData,
+        /// PSP22 token used for the rewards, the same as in the reward manager.
+        /// None when the rewards are paid in native currency
+        reward_token: Lazy<Option<AccountId>>,
+        /// number of distinct attestors who must submit the same response.
+        /// If zero or one, the first response is accepted
+        quorum_threshold: Lazy<u8>,
+        /// attestors and hash of the responses submitted by era (and by pool),
+        /// removed once the quorum is reached
+        quorum_submissions: Mapping<PoolKey<u32>, Vec<(AccountId, Hash)>>,
+        /// number of blocks during which the result of a raffle can be cancelled.
+        /// If zero, the rewards are released when the result is received
+        dispute_window: Lazy<BlockNumber>,
+        /// results waiting for the end of the dispute window by era (and by pool)
+        pending_raffles: Mapping<PoolKey<u32>, PendingRaffle>,
+        /// version of the storage layout, none before the first migration
+        storage_version: Lazy<u16>,
+        timelock: TimelockData,
+        pausable: PausableData,
+        /// address receiving the remainder of the raffles when this destination is chosen
+        treasury_address: Lazy<AccountId>,
+        /// audit data sent with the result of the raffle by era (and by pool)
+        raffle_audits: Mapping<PoolKey<u32>, RaffleAudit>,
+        /// identifier of the chain, part of the domain separator of the responses
+        chain_id: Lazy<Hash>,
+        /// attestation evidence by attestor.
+        /// The attestors granted without evidence have an empty evidence and the default validity
+        attestors_info: Mapping<AccountId, AttestorInfo>,
+        /// attestors registered in the registry
+        attestors: Lazy<Vec<AccountId>>,
+        /// address of the dAppStaking developer contract for the pools other than the default one
+        pool_dapps_staking_developer_addresses: Mapping<PoolId, AccountId>,
+        /// pools registered in addition to the default one
+        pools: Lazy<Vec<PoolId>>,
+    }
+
+    impl Contract {
+        /// The min delay is the number of blocks between the schedule and the execution of an operation
+        #[ink(constructor)]
+        pub fn new(
+            dapps_staking_developer_address: AccountId,
+            reward_manager_address: AccountId,
+            min_delay: u32,
+        ) -> Self {
+            let mut instance = Self::default();
+            let caller = instance.env().caller();
+            // set the admin of this contract
+            BaseAccessControl::init_with_admin(&mut instance, caller);
+            BaseAccessControl::inner_grant_role(&mut instance, RAFFLE_MANAGER_ROLE, caller)
+                .expect("Should grant the role RAFFLE_MANAGER_ROLE");
+            BaseAccessControl::inner_grant_role(&mut instance, GUARDIAN_ROLE, caller)
+                .expect("Should grant the role GUARDIAN_ROLE");
+            BaseTimelock::inner_set_min_delay(&mut instance, min_delay)
+                .expect("Should set the min delay");
+            instance.dapps_staking_developer_address = Some(dapps_staking_developer_address);
+            instance.reward_manager_address = Some(reward_manager_address);
+            instance.storage_version.set(&STORAGE_VERSION);
+            instance
+        }
+
+        /// Record the response submitted by the attestor for the given pool and era.
+        /// A new submission of the attestor replaces the previous one.
+        /// Return true if enough active attestors submitted the same response
+        fn check_quorum(
+            &mut self,
+            pool_id: PoolId,
+            era: u32,
+            attestor: AccountId,
+            payload: &[u8],
+        ) -> Result<bool, RaffleError> {
+            let quorum_threshold = self.quorum_threshold.get().unwrap_or_default();
+            if quorum_threshold <= 1 {
+                return Ok(true);
+            }
+
+            // only the next era is expected
+            if self.inner_get_next_era(pool_id)? != era {
+                return Err(RaffleError::IncorrectEra);
+            }
+
+            let payload_hash = Hash::from(self.env().hash_bytes::<Blake2x256>(payload));
+
+            let mut submissions = self
+                .quorum_submissions
+                .get(PoolKey::new(pool_id, era))
+                .unwrap_or_default();
+            // the attestor can correct its submission
+            submissions.retain(|(a, _)| *a != attestor);
+            // the submissions of the attestors revoked or expired are not counted
+            submissions.retain(|(a, _)| self.is_active_attestor(*a));
+            if submissions.iter().any(|(_, h)| *h != payload_hash) {
+                // emit event ResponseDisagreement
+                self.env().emit_event(ResponseDisagreement {
+                    era,
+                    attestor,
+                    payload_hash,
+                });
+            }
+            submissions.push((attestor, payload_hash));
+
+            let nb_submissions = u8::try_from(
+                submissions.iter().filter(|(_, h)| *h == payload_hash).count(),
+            )?;
+            if nb_submissions >= quorum_threshold {
+                // the next submissions for this era are rejected because the era is done
+                self.quorum_submissions.remove(PoolKey::new(pool_id, era));
+                return Ok(true);
+            }
+            self.quorum_submissions
+                .insert(PoolKey::new(pool_id, era), &submissions);
+
+            // emit event ResponseSubmitted
+            self.env().emit_event(ResponseSubmitted {
+                era,
+                attestor,
+                payload_hash,
+                nb_submissions,
+            });
+            Ok(false)
+        }
+
+        /// Return true if the account is granted as attestor and its attestation is not expired
+        fn is_active_attestor(&self, account: AccountId) -> bool {
+            self.inner_has_role(ATTESTOR_ROLE, account)
+                && self.check_attestor_not_expired(account).is_ok()
+        }
+
+        /// Set the number of attestors who must submit the same response.
+        /// The threshold cannot be above the number of active attestors
+        #[ink(message)]
+        pub fn set_quorum_threshold(&mut self, quorum_threshold: u8) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+            self.check_quorum_threshold(quorum_threshold, self.get_active_attestors().len())?;
+            self.quorum_threshold.set(&quorum_threshold);
+            // emit event QuorumThresholdUpdated
+            self.env().emit_event(QuorumThresholdUpdated { quorum_threshold });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_quorum_threshold(&self) -> u8 {
+            self.quorum_threshold.get().unwrap_or_default()
+        }
+
+        /// Check the quorum can be reached with the given number of active attestors
+        fn check_quorum_threshold(
+            &self,
+            quorum_threshold: u8,
+            nb_attestors: usize,
+        ) -> Result<(), RaffleError> {
+            if quorum_threshold > 1 && usize::from(quorum_threshold) > nb_attestors {
+                return Err(RaffleError::QuorumThresholdTooHigh);
+            }
+            Ok(())
+        }
+
+        /// Return the attestors and the hash of the responses they submitted for the given era
+        #[ink(message)]
+        pub fn get_quorum_submissions(&self, era: u32) -> Vec<(AccountId, Hash)> {
+            self.get_pool_quorum_submissions(DEFAULT_POOL, era)
+        }
+
+        /// Return the attestors and the hash of the responses they submitted for the given pool and era
+        #[ink(message)]
+        pub fn get_pool_quorum_submissions(&self, pool_id: PoolId, era: u32) -> Vec<(AccountId, Hash)> {
+            self.quorum_submissions
+                .get(PoolKey::new(pool_id, era))
+                .unwrap_or_default()
+        }
+
+        /// Save the result of the raffle of the pool.
+        /// Return the rewards to release now, None if the raffle is skipped
+        /// or if the rewards wait for the end of the dispute window
+        pub fn save_response(
+            &mut self,
+            pool_id: PoolId,
+            response: &RaffleResponseMessage,
+        ) -> Result<Option<RaffleRelease>, RaffleError> {
+            if let Some(reason) = response.skip_reason {
+                self.skip_raffle(pool_id, response.era, response.rewards, reason)?;
+                // the winners in cooldown can participate again
+                self.update_winners_in_cooldown(
+                    pool_id,
+                    response.era.checked_add(1).ok_or(RaffleError::AddOverFlow)?,
+                );
+                // emit event RaffleSkipped
+                self.env().emit_event(RaffleSkipped {
+                    contract: self.env().account_id(),
+                    pool_id,
+                    era: response.era,
+                    reason,
+                });
+
+                return Ok(None);
+            }
+
+            let previous_jackpot = self.inner_get_jackpot(pool_id);
+            let winners_filter = self.snapshot_winners_filter(pool_id);
+            let (winners_rewards, remainder, protocol_fee) = self.mark_raffle_done(
+                pool_id,
+                response.era,
+                response.rewards,
+                &response.winners,
+                response.winners_proof.as_ref(),
+            )?;
+
+            // save the winners, the event WinnerDrawn is emitted when the rewards are released
+            let winners: Vec<AccountId> = winners_rewards.iter().map(|(account, _)| *account).collect();
+            self.add_winners(pool_id, &winners, response.era);
+
+            let remainder_destination = self.get_effective_remainder_destination(pool_id);
+
+            let dispute_window = self.dispute_window.get().unwrap_or_default();
+            if dispute_window == 0 {
+                return Ok(Some(RaffleRelease {
+                    era: response.era,
+                    rewards: response.rewards,
+                    winners: response.winners.clone(),
+                    winners_rewards,
+                    remainder,
+                    remainder_destination,
+                    protocol_fee,
+                }));
+            }
+
+            // the rewards are released at the end of the dispute window
+            let release_block = self
+                .env()
+                .block_number()
+                .checked_add(dispute_window)
+                .ok_or(RaffleError::AddOverFlow)?;
+            let key = PoolKey::new(pool_id, response.era);
+            self.pending_raffles.insert(
+                key,
+                &PendingRaffle {
+                    rewards: response.rewards,
+                    winners: response.winners.clone(),
+                    winners_rewards,
+                    previous_jackpot,
+                    release_block,
+                    remainder,
+                    remainder_destination,
+                    protocol_fee,
+                    winners_filter,
+                },
+            );
+
+            // emit event RafflePending
+            self.env().emit_event(RafflePending {
+                contract: self.env().account_id(),
+                pool_id,
+                era: response.era,
+                release_block,
+            });
+
+            Ok(None)
+        }
+
+        /// Save the results of contiguous eras of the pool, starting from the next era,
+        /// and release the rewards of all raffles at once
+        pub fn save_responses(
+            &mut self,
+            pool_id: PoolId,
+            responses: &[RaffleResponseMessage],
+        ) -> Result<(), RaffleError> {
+            let mut releases = Vec::with_capacity(responses.len());
+            let mut expected_era = self.inner_get_next_era(pool_id)?;
+            for response in responses {
+                if response.era != expected_era {
+                    return Err(RaffleError::IncorrectEra);
+                }
+                if let Some(release) = self.save_response(pool_id, response)? {
+                    releases.push(release);
+                }
+                expected_era = expected_era.checked_add(1).ok_or(RaffleError::AddOverFlow)?;
+            }
+            self.release_rewards(pool_id, releases)
+        }
+
+        /// Withdraw the rewards from the developer contract of the pool, fund the reward manager
+        /// and send the protocol fee and the remainder (if this destination is chosen) to the treasury.
+        /// The rewards of several raffles are released with a single withdrawal and a single funding
+        fn release_rewards(
+            &mut self,
+            pool_id: PoolId,
+            releases: Vec<RaffleRelease>,
+        ) -> Result<(), RaffleError> {
+            if releases.is_empty() {
+                return Ok(());
+            }
+
+            let mut given_rewards : Balance = 0;
+            // the protocol fee and the remainder sent to the treasury are withdrawn with the rewards
+            let mut treasury_amount : Balance = 0;
+            // the rewards are funded with their own era
+            let mut rewards_by_era = Vec::new();
+            for release in &releases {
+                for (account, amount) in &release.winners_rewards {
+                    given_rewards = given_rewards.checked_add(*amount).ok_or(RaffleError::AddOverFlow)? ;
+                    rewards_by_era.push((release.era, *account, *amount));
+                }
+
+                treasury_amount = treasury_amount
+                    .checked_add(release.protocol_fee)
+                    .ok_or(RaffleError::AddOverFlow)?;
+                if release.remainder_destination == Some(RemainderDestination::Treasury) {
+                    treasury_amount = treasury_amount
+                        .checked_add(release.remainder)
+                        .ok_or(RaffleError::AddOverFlow)?;
+                }
+            }
+
+            let treasury_address = if treasury_amount > 0 {
+                Some(
+                    self.treasury_address
+                        .get()
+                        .ok_or(RaffleError::TreasuryAddressMissing)?,
+                )
+            } else {
+                None
+            };
+            let reward_manager_address = self
+                .reward_manager_address
+                .ok_or(RaffleError::RewardManagerAddressMissing)?;
+
+            // in token mode, the rewards are paid with the tokens held by this contract
+            // and pulled by the reward manager, otherwise they are withdrawn from developer dAppsStaking
+            let reward_token = self.reward_token.get().flatten();
+            let transferred_value = match reward_token {
+                Some(token) => {
+                    self.approve_token(token, reward_manager_address, given_rewards)?;
+                    0
+                }
+                None => {
+                    let withdrawn_rewards = given_rewards
+                        .checked_add(treasury_amount)
+                        .ok_or(RaffleError::AddOverFlow)?;
+                    self.withdraw_from_developer(pool_id, withdrawn_rewards)?;
+                    given_rewards
+                }
+            };
+
+            // set the list of winners and fund the rewards
+            debug_message("call reward manager contract");
+            ink::env::call::build_call::<Environment>()
+                .call(reward_manager_address)
+                .call_v1()
+                .transferred_value(transferred_value)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(FUND_REWARDS_BY_ERA_SELECTOR))
+                        .push_arg(rewards_by_era),
+                )
+                .returns::<Result<(), RewardError>>()
+                .try_invoke()
+                .map_err(|_| RaffleError::CrossContractCallEnvError)?
+                .map_err(|_| RaffleError::CrossContractCallLangError)??;
+
+            // send the protocol fee and the remainder to the treasury
+            if let Some(treasury_address) = treasury_address {
+                match reward_token {
+                    Some(token) => self.transfer_token(token, treasury_address, treasury_amount)?,
+                    None => self
+                        .env()
+                        .transfer(treasury_address, treasury_amount)
+                        .map_err(|_| RaffleError::TransferError)?,
+                }
+            }
+
+            // emit events WinnerDrawn and RaffleDone for each era
+            for release in releases {
+                for (account, amount) in &release.winners_rewards {
+                    let rank = release
+                        .winners
+                        .iter()
+                        .position(|w| w == account)
+                        .unwrap_or_default()
+                        .checked_add(1)
+                        .ok_or(RaffleError::AddOverFlow)?;
+                    self.env().emit_event(WinnerDrawn {
+                        pool_id,
+                        era: release.era,
+                        rank: u16::try_from(rank)?,
+                        account: *account,
+                        amount: *amount,
+                    });
+                }
+                self.env().emit_event(RaffleDone {
+                    contract: self.env().account_id(),
+                    pool_id,
+                    era: release.era,
+                    nb_winners: u16::try_from(release.winners_rewards.len())?,
+                    pending_rewards: release.rewards,
+                    remainder: release.remainder,
+                    remainder_destination: release.remainder_destination,
+                    protocol_fee: release.protocol_fee,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Withdraw the given value from the developer dAppsStaking contract of the pool
+        fn withdraw_from_developer(&mut self, pool_id: PoolId, value: Balance) -> Result<(), RaffleError> {
+            let dapps_staking_developer_address = self
+                .inner_get_dapps_staking_developer_address(pool_id)
+                .ok_or(RaffleError::DappsStakingDeveloperAddressMissing)?;
+
+            debug_message("call dAppStaking dev contract");
+            ink::env::call::build_call::<Environment>()
+                .call(dapps_staking_developer_address)
+                .call_v1()
+                .exec_input(
+                    ExecutionInput::new(Selector::new(WITHDRAW_SELECTOR)).push_arg(value),
+                )
+                .returns::<Result<(), DappsStakingDeveloperError>>()
+                .try_invoke()
+                .map_err(|_| RaffleError::CrossContractCallEnvError)?
+                .map_err(|_| RaffleError::CrossContractCallLangError)??;
+            Ok(())
+        }
+
+        /// Allow the spender to transfer the given value of PSP22 token from this contract
+        fn approve_token(&mut self, token: AccountId, spender: AccountId, value: Balance) -> Result<(), RaffleError> {
+            ink::env::call::build_call::<Environment>()
+                .call(token)
+                .call_v1()
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_APPROVE_SELECTOR))
+                        .push_arg(spender)
+                        .push_arg(value),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| RaffleError::CrossContractCallEnvError)?
+                .map_err(|_| RaffleError::CrossContractCallLangError)??;
+            Ok(())
+        }
+
+        /// Transfer the given value of PSP22 token from this contract
+        fn transfer_token(&mut self, token: AccountId, to: AccountId, value: Balance) -> Result<(), RaffleError> {
+            ink::env::call::build_call::<Environment>()
+                .call(token)
+                .call_v1()
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                        .push_arg(to)
+                        .push_arg(value)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| RaffleError::CrossContractCallEnvError)?
+                .map_err(|_| RaffleError::CrossContractCallLangError)??;
+            Ok(())
+        }
+
+        /// Return the PSP22 token used for the rewards, None if the rewards are paid in native currency
+        #[ink(message)]
+        pub fn get_reward_token(&self) -> Option<AccountId> {
+            self.reward_token.get().flatten()
+        }
+
+        /// Release the rewards of a raffle of the default pool when the dispute window is over
+        #[ink(message)]
+        pub fn finalize_raffle(&mut self, era: u32) -> Result<(), RaffleError> {
+            self.inner_finalize_raffle(DEFAULT_POOL, era)
+        }
+
+        /// Release the rewards of a raffle of the given pool when the dispute window is over
+        #[ink(message)]
+        pub fn finalize_pool_raffle(&mut self, pool_id: PoolId, era: u32) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_finalize_raffle(pool_id, era)
+        }
+
+        fn inner_finalize_raffle(&mut self, pool_id: PoolId, era: u32) -> Result<(), RaffleError> {
+            if self.inner_is_paused() {
+                return Err(RaffleError::Paused);
+            }
+
+            let key = PoolKey::new(pool_id, era);
+            let pending_raffle = self
+                .pending_raffles
+                .get(key)
+                .ok_or(RaffleError::NoPendingRaffle)?;
+
+            if self.env().block_number() <= pending_raffle.release_block {
+                return Err(RaffleError::DisputeWindowNotOver);
+            }
+
+            self.pending_raffles.remove(key);
+            self.release_rewards(pool_id, vec![RaffleRelease {
+                era,
+                rewards: pending_raffle.rewards,
+                winners: pending_raffle.winners,
+                winners_rewards: pending_raffle.winners_rewards,
+                remainder: pending_raffle.remainder,
+                remainder_destination: pending_raffle.remainder_destination,
+                protocol_fee: pending_raffle.protocol_fee,
+            }])
+        }
+
+        /// Cancel the result of the last raffle of the default pool during the dispute window.
+        /// The raffle can be run again for this era.
+        #[ink(message)]
+        pub fn cancel_raffle(&mut self, era: u32, reason: String) -> Result<(), RaffleError> {
+            only_role!(self, RAFFLE_MANAGER_ROLE);
+            self.inner_cancel_raffle(DEFAULT_POOL, era, reason)
+        }
+
+        /// Cancel the result of the last raffle of the given pool during the dispute window
+        #[ink(message)]
+        pub fn cancel_pool_raffle(
+            &mut self,
+            pool_id: PoolId,
+            era: u32,
+            reason: String,
+        ) -> Result<(), RaffleError> {
+            only_role!(self, RAFFLE_MANAGER_ROLE);
+            self.check_pool(pool_id)?;
+            self.inner_cancel_raffle(pool_id, era, reason)
+        }
+
+        fn inner_cancel_raffle(
+            &mut self,
+            pool_id: PoolId,
+            era: u32,
+            reason: String,
+        ) -> Result<(), RaffleError> {
+            let key = PoolKey::new(pool_id, era);
+            let pending_raffle = self
+                .pending_raffles
+                .get(key)
+                .ok_or(RaffleError::NoPendingRaffle)?;
+
+            if self.env().block_number() > pending_raffle.release_block {
+                return Err(RaffleError::DisputeWindowOver);
+            }
+
+            // only the last raffle can be cancelled
+            if self.inner_get_next_era(pool_id)? != era.checked_add(1).ok_or(RaffleError::AddOverFlow)? {
+                return Err(RaffleError::IncorrectEra);
+            }
+
+            self.pending_raffles.remove(key);
+            self.raffle_audits.remove(key);
+            self.quorum_submissions.remove(key);
+            self.remove_raffle_result(pool_id, era);
+            self.set_jackpot(pool_id, pending_raffle.previous_jackpot);
+            // the winners of the cancelled raffle can participate again
+            self.restore_winners_filter(pool_id, pending_raffle.winners_filter);
+            self.inner_set_next_era_unchecked(pool_id, era)?;
+
+            // emit event RaffleCancelled
+            self.env().emit_event(RaffleCancelled {
+                contract: self.env().account_id(),
+                pool_id,
+                era,
+                reason,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn set_dispute_window(&mut self, dispute_window: BlockNumber) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+            self.dispute_window.set(&dispute_window);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_dispute_window(&self) -> BlockNumber {
+            self.dispute_window.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_pending_raffle(&self, era: u32) -> Option<PendingRaffle> {
+            self.pending_raffles.get(PoolKey::new(DEFAULT_POOL, era))
+        }
+
+        #[ink(message)]
+        pub fn get_pool_pending_raffle(&self, pool_id: PoolId, era: u32) -> Option<PendingRaffle> {
+            self.pending_raffles.get(PoolKey::new(pool_id, era))
+        }
+
+        /// Return the audit data sent with the result of the raffle for the given era
+        #[ink(message)]
+        pub fn get_raffle_audit(&self, era: u32) -> Option<RaffleAudit> {
+            self.raffle_audits.get(PoolKey::new(DEFAULT_POOL, era))
+        }
+
+        #[ink(message)]
+        pub fn get_pool_raffle_audit(&self, pool_id: PoolId, era: u32) -> Option<RaffleAudit> {
+            self.raffle_audits.get(PoolKey::new(pool_id, era))
+        }
+
+        /// Return the commitment on the random value used to draw the winners of the given era
+        #[ink(message)]
+        pub fn get_pool_random_commitment(&self, pool_id: PoolId, era: u32) -> Option<HashValue> {
+            self.inner_get_random_commitment(pool_id, era)
+        }
+
+        /// Return the root of the snapshot of the participants used to draw the winners of the given era
+        #[ink(message)]
+        pub fn get_pool_snapshot_commitment(&self, pool_id: PoolId, era: u32) -> Option<HashValue> {
+            self.inner_get_snapshot_commitment(pool_id, era)
+        }
+
+        /// Schedule the operation, it can be executed by anyone after the min delay
+        #[ink(message)]
+        pub fn schedule_operation(&mut self, operation: Operation) -> Result<(), RaffleError> {
+            self.inner_schedule_operation(&operation)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_operation(&mut self, operation: Operation) -> Result<(), RaffleError> {
+            self.inner_cancel_operation(&operation)?;
+            Ok(())
+        }
+
+        /// Execute the operation when the min delay is over
+        #[ink(message)]
+        pub fn execute_operation(&mut self, operation: Operation) -> Result<(), RaffleError> {
+            if let Some(operation) = self.inner_execute_operation(operation)? {
+                self.execute_raffle_operation(operation)?;
+            }
+            Ok(())
+        }
+
+        /// Deprecated: use the operation `SetDappsStakingDeveloperAddress`.
+        /// The first call schedules the operation, the call after the min delay executes it
+        #[ink(message)]
+        pub fn set_dapps_staking_developer_address(
+            &mut self,
+            address: AccountId,
+        ) -> Result<(), RaffleError> {
+            self.schedule_or_execute_raffle_operation(
+                RaffleOperation::SetDappsStakingDeveloperAddress(address),
+            )
+        }
+
+        /// Deprecated: use the operation `SetRewardManagerAddress`.
+        /// The first call schedules the operation, the call after the min delay executes it
+        #[ink(message)]
+        pub fn set_reward_manager_address(&mut self, address: AccountId) -> Result<(), RaffleError> {
+            self.schedule_or_execute_raffle_operation(RaffleOperation::SetRewardManagerAddress(address))
+        }
+
+        /// Deprecated: use the operation `Withdraw`.
+        /// The first call schedules the operation, the call after the min delay executes it
+        #[ink(message)]
+        pub fn withdraw(&mut self, value: Balance) -> Result<(), RaffleError> {
+            let caller = self.env().caller();
+            self.schedule_or_execute_raffle_operation(RaffleOperation::Withdraw(caller, value))
+        }
+
+        /// Deprecated: use the operation `TerminateContract`.
+        /// The first call schedules the operation, the call after the min delay executes it
+        #[ink(message)]
+        pub fn terminate_me(&mut self) -> Result<(), RaffleError> {
+            let caller = self.env().caller();
+            self.inner_schedule_or_execute_operation(Operation::TerminateContract(caller))?;
+            Ok(())
+        }
+
+        fn schedule_or_execute_raffle_operation(
+            &mut self,
+            operation: RaffleOperation,
+        ) -> Result<(), RaffleError> {
+            if let Some(operation) =
+                self.inner_schedule_or_execute_operation(Operation::Contract(operation))?
+            {
+                self.execute_raffle_operation(operation)?;
+            }
+            Ok(())
+        }
+
+        fn execute_raffle_operation(&mut self, operation: RaffleOperation) -> Result<(), RaffleError> {
+            match operation {
+                RaffleOperation::Withdraw(to, value) => {
+                    self.env()
+                        .transfer(to, value)
+                        .map_err(|_| RaffleError::TransferError)?;
+                }
+                RaffleOperation::SetDappsStakingDeveloperAddress(address) => {
+                    self.dapps_staking_developer_address = Some(address);
+                    // emit event DappsStakingDeveloperAddressUpdated
+                    self.env().emit_event(DappsStakingDeveloperAddressUpdated {
+                        pool_id: DEFAULT_POOL,
+                        address,
+                    });
+                }
+                RaffleOperation::SetRewardManagerAddress(address) => {
+                    self.reward_manager_address = Some(address);
+                    // emit event RewardManagerAddressUpdated
+                    self.env().emit_event(RewardManagerAddressUpdated { address });
+                }
+                RaffleOperation::SetRewardToken(token) => {
+                    self.reward_token.set(&token);
+                    // emit event RewardTokenUpdated
+                    self.env().emit_event(RewardTokenUpdated { token });
+                }
+                RaffleOperation::SetTreasuryAddress(address) => {
+                    self.treasury_address.set(&address);
+                    // emit event TreasuryAddressUpdated
+                    self.env().emit_event(TreasuryAddressUpdated { address });
+                }
+                RaffleOperation::SetChainId(chain_id) => {
+                    self.chain_id.set(&chain_id);
+                    // emit event ChainIdUpdated
+                    self.env().emit_event(ChainIdUpdated { chain_id });
+                }
+                RaffleOperation::SetPoolDappsStakingDeveloperAddress(pool_id, address) => {
+                    if pool_id == DEFAULT_POOL {
+                        self.dapps_staking_developer_address = Some(address);
+                    } else {
+                        self.pool_dapps_staking_developer_addresses.insert(pool_id, &address);
+                        let mut pools = self.pools.get().unwrap_or_default();
+                        if !pools.contains(&pool_id) {
+                            pools.push(pool_id);
+                            self.pools.set(&pools);
+                        }
+                    }
+                    // emit event DappsStakingDeveloperAddressUpdated
+                    self.env().emit_event(DappsStakingDeveloperAddressUpdated {
+                        pool_id,
+                        address,
+                    });
+                }
+                RaffleOperation::RemovePool(pool_id) => {
+                    let mut pools = self.pools.get().unwrap_or_default();
+                    if pool_id == DEFAULT_POOL || !pools.contains(&pool_id) {
+                        return Err(RaffleError::UnknownPool);
+                    }
+                    // the results, the jackpot and the settings of the pool are kept
+                    // and used again if the pool is registered again
+                    pools.retain(|p| *p != pool_id);
+                    self.pools.set(&pools);
+                    self.pool_dapps_staking_developer_addresses.remove(pool_id);
+                    // emit event PoolRemoved
+                    self.env().emit_event(PoolRemoved { pool_id });
+                }
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_dapps_staking_developer_address(&mut self) -> Option<AccountId> {
+            self.dapps_staking_developer_address
+        }
+
+        #[ink(message)]
+        pub fn get_pool_dapps_staking_developer_address(&self, pool_id: PoolId) -> Option<AccountId> {
+            self.inner_get_dapps_staking_developer_address(pool_id)
+        }
+
+        fn inner_get_dapps_staking_developer_address(&self, pool_id: PoolId) -> Option<AccountId> {
+            if pool_id == DEFAULT_POOL {
+                return self.dapps_staking_developer_address;
+            }
+            self.pool_dapps_staking_developer_addresses.get(pool_id)
+        }
+
+        /// Return the pools registered in addition to the default one
+        #[ink(message)]
+        pub fn get_pools(&self) -> Vec<PoolId> {
+            self.pools.get().unwrap_or_default()
+        }
+
+        /// Check the pool is the default one or has been registered
+        fn check_pool(&self, pool_id: PoolId) -> Result<(), RaffleError> {
+            if pool_id == DEFAULT_POOL || self.get_pools().contains(&pool_id) {
+                Ok(())
+            } else {
+                Err(RaffleError::UnknownPool)
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_reward_manager_address(&mut self) -> Option<AccountId> {
+            self.reward_manager_address
+        }
+
+        #[ink(message)]
+        pub fn get_treasury_address(&self) -> Option<AccountId> {
+            self.treasury_address.get()
+        }
+
+        /// Return the domain separator the responses must include, None if the chain id is not set
+        #[ink(message)]
+        pub fn get_domain_separator(&self) -> Option<DomainSeparator> {
+            self.chain_id.get().map(|chain_id| DomainSeparator {
+                chain_id,
+                contract: self.env().account_id(),
+            })
+        }
+
+        /// Check the response has been produced for this deployment.
+        /// Until the chain id is set, only the contract is checked
+        fn check_domain(&self, domain: &DomainSeparator) -> Result<(), RaffleError> {
+            if domain.contract != self.env().account_id() {
+                return Err(RaffleError::InvalidDomain);
+            }
+            match self.chain_id.get() {
+                Some(chain_id) if chain_id != domain.chain_id => Err(RaffleError::InvalidDomain),
+                _ => Ok(()),
+            }
+        }
+
+        /// Register the attestor without evidence, it is rejected after the default validity
+        #[ink(message)]
+        pub fn register_attestor(
+            &mut self,
+            account_id: AccountId,
+        ) -> Result<(), AccessControlError> {
+            only_role!(self, ADMIN_ROLE);
+            AccessControl::grant_role(self, ATTESTOR_ROLE, account_id)?;
+            Ok(())
+        }
+
+        /// Register the attestor with the evidence of its attestation.
+        /// The attestor is rejected after `validity` blocks
+        #[ink(message)]
+        pub fn register_attestor_with_evidence(
+            &mut self,
+            account_id: AccountId,
+            quote_hash: Hash,
+            measurement: Hash,
+            validity: BlockNumber,
+        ) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+            self.inner_register_attestor(account_id, quote_hash, measurement, validity)
+        }
+
+        /// Register the new attestor and revoke the previous one in the same transaction,
+        /// so that there is always an attestor able to send the responses.
+        /// If the key is the same, the attestation is only renewed
+        #[ink(message)]
+        pub fn rotate_attestor(
+            &mut self,
+            old_account_id: AccountId,
+            new_account_id: AccountId,
+            quote_hash: Hash,
+            measurement: Hash,
+            validity: BlockNumber,
+        ) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+            self.inner_register_attestor(new_account_id, quote_hash, measurement, validity)?;
+            if old_account_id != new_account_id {
+                self.inner_revoke_attestor(old_account_id)?;
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn revoke_attestor(&mut self, account_id: AccountId) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+            self.inner_revoke_attestor(account_id)
+        }
+
+        #[ink(message)]
+        pub fn get_attestor_info(&self, account_id: AccountId) -> Option<AttestorInfo> {
+            self.attestors_info.get(account_id)
+        }
+
+        /// Return the attestors registered with their evidence, granted and not expired
+        #[ink(message)]
+        pub fn get_active_attestors(&self) -> Vec<(AccountId, AttestorInfo)> {
+            let block_number = self.env().block_number();
+            self.attestors
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|attestor| self.inner_has_role(ATTESTOR_ROLE, *attestor))
+                .filter_map(|attestor| {
+                    self.attestors_info
+                        .get(attestor)
+                        .filter(|info| info.expires_at > block_number)
+                        .map(|info| (attestor, info))
+                })
+                .collect()
+        }
+
+        fn inner_register_attestor(
+            &mut self,
+            account_id: AccountId,
+            quote_hash: Hash,
+            measurement: Hash,
+            validity: BlockNumber,
+        ) -> Result<(), RaffleError> {
+            let registered_at = self.env().block_number();
+            let expires_at = registered_at
+                .checked_add(validity)
+                .ok_or(RaffleError::AddOverFlow)?;
+
+            // the attestation of an attestor already granted is renewed
+            if !self.inner_has_role(ATTESTOR_ROLE, account_id) {
+                self.inner_grant_role(ATTESTOR_ROLE, account_id)?;
+            }
+            self.save_attestor_info(account_id, quote_hash, measurement, registered_at, expires_at);
+            Ok(())
+        }
+
+        fn save_attestor_info(
+            &mut self,
+            account_id: AccountId,
+            quote_hash: Hash,
+            measurement: Hash,
+            registered_at: BlockNumber,
+            expires_at: BlockNumber,
+        ) {
+            self.attestors_info.insert(
+                account_id,
+                &AttestorInfo {
+                    quote_hash,
+                    measurement,
+                    registered_at,
+                    expires_at,
+                },
+            );
+            let mut attestors = self.attestors.get().unwrap_or_default();
+            if !attestors.contains(&account_id) {
+                attestors.push(account_id);
+                self.attestors.set(&attestors);
+            }
+
+            // emit event AttestorRegistered
+            self.env().emit_event(AttestorRegistered {
+                attestor: account_id,
+                quote_hash,
+                measurement,
+                expires_at,
+            });
+        }
+
+        fn inner_revoke_attestor(&mut self, account_id: AccountId) -> Result<(), RaffleError> {
+            // the quorum must still be reachable without this attestor
+            let active_attestors = self.get_active_attestors();
+            if active_attestors.iter().any(|(attestor, _)| *attestor == account_id) {
+                self.check_quorum_threshold(
+                    self.quorum_threshold.get().unwrap_or_default(),
+                    active_attestors.len().saturating_sub(1),
+                )?;
+            }
+
+            self.inner_revoke_role(ATTESTOR_ROLE, account_id)?;
+            self.attestors_info.remove(account_id);
+            let mut attestors = self.attestors.get().unwrap_or_default();
+            attestors.retain(|attestor| *attestor != account_id);
+            self.attestors.set(&attestors);
+
+            // emit event AttestorRevoked
+            self.env().emit_event(AttestorRevoked {
+                attestor: account_id,
+            });
+            Ok(())
+        }
+
+        /// Reject the attestor if its attestation is expired.
+        /// The attestors granted before the registry have no attestation, they are accepted
+        /// until they are registered or rotated
+        fn check_attestor_not_expired(&self, attestor: AccountId) -> Result<(), RaffleError> {
+            match self.attestors_info.get(attestor) {
+                Some(info) if self.env().block_number() >= info.expires_at => {
+                    Err(RaffleError::AttestorExpired)
+                }
+                _ => Ok(()),
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_attestor_role(&self) -> RoleType {
+            ATTESTOR_ROLE
+        }
+
+        /// Migrate the storage written by the previous code to the current layout.
+        /// Must be called after the operation `UpgradeContract`.
+        /// The raffle data, the winners filter, the kv store and the roles are kept.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), RaffleError> {
+            only_role!(self, ADMIN_ROLE);
+
+            let from_version = self.storage_version.get().unwrap_or_default();
+            if from_version >= STORAGE_VERSION {
+                return Err(RaffleError::AlreadyMigrated);
+            }
+
+            if from_version < 1 {
+                // publish in the kv store the data read by the worker
+                self.save_nb_winners(DEFAULT_POOL)?;
+                self.set_jackpot(DEFAULT_POOL, self.inner_get_jackpot(DEFAULT_POOL));
+                self.save_protocol_fee(DEFAULT_POOL);
+                self.save_excluded_addresses(DEFAULT_POOL);
+                // the admin can pause the contract
+                let caller = self.env().caller();
+                if !self.inner_has_role(GUARDIAN_ROLE, caller) {
+                    self.inner_grant_role(GUARDIAN_ROLE, caller)?;
+                }
+            }
+
+            self.storage_version.set(&STORAGE_VERSION);
+
+            // emit event StorageMigrated
+            self.env().emit_event(StorageMigrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+            });
+
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u16 {
+            self.storage_version.get().unwrap_or_default()
+        }
+
+    }
+
+    /// Result of the raffle for an era, as sent by the first version of the worker (without envelope)
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct RaffleResponseMessageV1 {
+        pub era: u32,
+        pub skipped: bool,
+        pub rewards: Balance,
+        pub winners: Vec<AccountId>,
+    }
+
+    /// convertor from RaffleResponseMessageV1 to RaffleResponseMessage
+    /// The first version of the worker only skipped the raffles during the voting sub period
+    impl From<RaffleResponseMessageV1> for RaffleResponseMessage {
+        fn from(message: RaffleResponseMessageV1) -> Self {
+            RaffleResponseMessage {
+                era: message.era,
+                skip_reason: message.skipped.then_some(SkipReason::VotingSubPeriod),
+                rewards: message.rewards,
+                winners: message.winners,
+                winners_proof: None,
+            }
+        }
+    }
+
+    /// Result of the raffle for an era
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct RaffleResponseMessage {
+        pub era: u32,
+        /// reason why the raffle is skipped, None if the raffle is done
+        pub skip_reason: Option<SkipReason>,
+        pub rewards: Balance,
+        pub winners: Vec<AccountId>,
+        /// proof the winners have been drawn among the participants
+        pub winners_proof: Option<WinnersProof>,
+    }
+
+    /// Data sent by the worker to audit the raffle
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct RaffleAudit {
+        pub nb_participants: u32,
+        pub total_tickets: u128,
+        /// hash of the snapshot of the participants
+        pub snapshot_hash: Hash,
+        /// output of the randomness used to draw the winners
+        pub randomness: Hash,
+    }
+
+    /// Result of the raffle for an era with the audit data, if any,
+    /// the commitment on the random value of the era `era + RANDOM_COMMITMENT_ERA_OFFSET`, if any,
+    /// and the root of the snapshot of the participants of the era `era + SNAPSHOT_COMMITMENT_ERA_OFFSET`, if any
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct RaffleResponseMessageV2 {
+        pub response: RaffleResponseMessage,
+        pub audit: Option<RaffleAudit>,
+        pub next_random_commitment: Option<HashValue>,
+        pub next_snapshot_commitment: Option<HashValue>,
+    }
+
+    /// Versioned envelope of the results sent by the worker.
+    /// The optional data are added as optional fields in the current version,
+    /// the first version is kept for the messages sent by the worker deployed before the upgrade.
+    #[ink::scale_derive(Encode, Decode)]
+    pub enum RaffleResponse {
+        /// results of contiguous eras of the default pool, in the format of the first version of the worker
+        V1(Vec<RaffleResponseMessageV1>),
+        /// results of contiguous eras of the given pool (the default pool if None)
+        V2 {
+            pool_id: Option<PoolId>,
+            responses: Vec<RaffleResponseMessageV2>,
+        },
+    }
+
+    /// Deployment the response has been produced for
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct DomainSeparator {
+        /// identifier of the chain (ie the genesis hash)
+        pub chain_id: Hash,
+        /// address of the raffle_consumer contract
+        pub contract: AccountId,
+    }
+
+    /// Response bound to a deployment so that it cannot be replayed on another one
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct BoundRaffleResponse {
+        pub domain: DomainSeparator,
+        pub response: RaffleResponse,
+    }
+
+    /// Prefix of the actions sent in the envelope.
+    /// The messages sent without envelope start with the era (u32 little endian),
+    /// so their fourth byte is never 0xff
+    pub const RESPONSE_ENVELOPE_PREFIX: [u8; 4] = *b"LKY\xff";
+
+    /// Envelope of the actions sent by the worker
+    #[ink::scale_derive(Encode, Decode)]
+    pub enum ResponseEnvelope {
+        /// response accepted while the chain id is not set
+        Unbound(RaffleResponse),
+        /// response bound to a deployment
+        Bound(BoundRaffleResponse),
+    }
+
+    impl ResponseEnvelope {
+        /// Encode the envelope with its prefix
+        pub fn encode_action(&self) -> Vec<u8> {
+            let mut action = RESPONSE_ENVELOPE_PREFIX.to_vec();
+            self.encode_to(&mut action);
+            action
+        }
+
+        /// Decode the envelope identified by its prefix.
+        /// The single result sent without envelope by the first version of the worker
+        /// is only decoded during the migration window (ie `accept_legacy` is true)
+        pub fn decode_action(action: &[u8], accept_legacy: bool) -> Option<Self> {
+            match action.strip_prefix(&RESPONSE_ENVELOPE_PREFIX[..]) {
+                Some(mut envelope) => Self::decode_all(&mut envelope).ok(),
+                None if accept_legacy => RaffleResponseMessageV1::decode_all(&mut &action[..])
+                    .ok()
+                    .map(|response| ResponseEnvelope::Unbound(RaffleResponse::V1(vec![response]))),
+                None => None,
+            }
+        }
+    }
+
+    /// Content of a response, whatever its version
+    #[derive(Default)]
+    pub struct RaffleResponseContent {
+        pub pool_id: PoolId,
+        pub responses: Vec<RaffleResponseMessage>,
+        /// audit data by era
+        pub audits: Vec<(u32, RaffleAudit)>,
+        /// commitment on the random value by era
+        pub random_commitments: Vec<(u32, HashValue)>,
+        /// root of the snapshot of the participants by era
+        pub snapshot_commitments: Vec<(u32, HashValue)>,
     }
 
-    impl Contract {
-        #[ink(constructor)]
-        pub fn new(
-            dapps_staking_developer_address: AccountId,
-            reward_manager_address: AccountId,
-        ) -> Self {
-            let mut instance = Self::default();
-            let caller = instance.env().caller();
-            // set the admin of this contract
-            BaseAccessControl::init_with_admin(&mut instance, caller);
-            BaseAccessControl::inner_grant_role(&mut instance, RAFFLE_MANAGER_ROLE, caller)
-                .expect("Should grant the role RAFFLE_MANAGER_ROLE");
-            instance.dapps_staking_developer_address = Some(dapps_staking_developer_address);
-            instance.reward_manager_address = Some(reward_manager_address);
-            instance
+    impl RaffleResponse {
+        /// Split the pool, the results, the audit data and the commitments on the random values.
+        /// The results sent before the pools were introduced are for the default pool
+        pub fn into_content(self) -> Result<RaffleResponseContent, RaffleError> {
+            let mut content = RaffleResponseContent::default();
+            match self {
+                RaffleResponse::V1(messages) => {
+                    content.pool_id = DEFAULT_POOL;
+                    content.responses = messages.into_iter().map(RaffleResponseMessage::from).collect();
+                }
+                RaffleResponse::V2 { pool_id, responses: messages } => {
+                    content.pool_id = pool_id.unwrap_or(DEFAULT_POOL);
+                    for message in messages {
+                        if let Some(audit) = message.audit {
+                            content.audits.push((message.response.era, audit));
+                        }
+                        if let Some(commitment) = message.next_random_commitment {
+                            let era = message
+                                .response
+                                .era
+                                .checked_add(RANDOM_COMMITMENT_ERA_OFFSET)
+                                .ok_or(RaffleError::AddOverFlow)?;
+                            content.random_commitments.push((era, commitment));
+                        }
+                        if let Some(snapshot_root) = message.next_snapshot_commitment {
+                            let era = message
+                                .response
+                                .era
+                                .checked_add(SNAPSHOT_COMMITMENT_ERA_OFFSET)
+                                .ok_or(RaffleError::AddOverFlow)?;
+                            content.snapshot_commitments.push((era, snapshot_root));
+                        }
+                        content.responses.push(message.response);
+                    }
+                }
+            }
+            Ok(content)
         }
+    }
 
-        pub fn save_response(
-            &mut self,
-            response: &RaffleResponseMessage,
-        ) -> Result<(), RaffleError> {
-            if response.skipped {
-                self.skip_raffle(response.era)?;
-                // emit event RaffleSkipped
-                self.env().emit_event(RaffleSkipped {
-                    contract: self.env().caller(),
-                    era: response.era,
-                });
+    impl Contract {
+        /// Handle the response sent by the attestor
+        fn handle_response(&mut self, attestor: AccountId, action: Vec<u8>) -> Result<(), RollupClientError> {
 
-                return Ok(());
+            // reject the responses while the contract is paused
+            if self.inner_is_paused() {
+                return Err(RaffleError::Paused.into());
             }
 
-            let winners_rewards =
-                self.mark_raffle_done(response.era, response.rewards, &response.winners)?;
+            // parse the response: a single era or a batch of contiguous eras.
+            // The messages without envelope are accepted until the chain id is set (ie the end of the migration)
+            let accept_legacy = self.chain_id.get().is_none();
+            let envelope = ResponseEnvelope::decode_action(&action, accept_legacy)
+                .ok_or(RollupClientError::FailedToDecode)?;
+            let response = match envelope {
+                ResponseEnvelope::Bound(bound_response) => {
+                    self.check_domain(&bound_response.domain)?;
+                    bound_response.response
+                }
+                ResponseEnvelope::Unbound(response) => {
+                    // the responses without domain are rejected once the chain id is set
+                    if !accept_legacy {
+                        return Err(RaffleError::MissingDomain.into());
+                    }
+                    response
+                }
+            };
+            // the format of the first version of the worker is only accepted during the migration
+            if matches!(response, RaffleResponse::V1(_)) && !accept_legacy {
+                return Err(RollupClientError::FailedToDecode);
+            }
+            let RaffleResponseContent { pool_id, responses, audits, random_commitments, snapshot_commitments } =
+                response.into_content()?;
+            self.check_pool(pool_id)?;
+            let era = responses
+                .first()
+                .map(|response| response.era)
+                .ok_or(RollupClientError::FailedToDecode)?;
+
+            // wait for the other attestors if the quorum is not reached.
+            // The audit data and the proofs depend on the worker, only the results and
+            // the commitments must be the same (ie the workers share the seed and the indexer)
+            let results: Vec<_> = responses
+                .iter()
+                .map(|r| (r.era, &r.skip_reason, r.rewards, &r.winners))
+                .collect();
+            let payload = (pool_id, results, &random_commitments, &snapshot_commitments).encode();
+            if !self.check_quorum(pool_id, era, attestor, &payload)? {
+                return Ok(());
+            }
 
-            let nb_winners = winners_rewards.len();
+            self.save_responses(pool_id, &responses)?;
 
-            // save the winners
-            let mut given_rewards : Balance = 0;
-            for winner in &winners_rewards {
-                self.add_winner(winner.0);
-                given_rewards = given_rewards.checked_add(winner.1).ok_or(RaffleError::AddOverFlow)? ;
+            // save the audit data
+            for (era, audit) in audits {
+                self.raffle_audits.insert(PoolKey::new(pool_id, era), &audit);
             }
 
-            // withdraw the rewards from developer dAppsStaking
-            let dapps_staking_developer_address = self
-                .dapps_staking_developer_address
-                .ok_or(RaffleError::DappsStakingDeveloperAddressMissing)?;
+            // save the commitments on the random values of the next eras
+            for (era, commitment) in random_commitments {
+                self.inner_commit_random(pool_id, era, commitment);
+            }
 
-            debug_message("call dAppStaking dev contract");
-            ink::env::call::build_call::<Environment>()
-                .call(dapps_staking_developer_address)
-                .call_v1()
-                .exec_input(
-                    ExecutionInput::new(Selector::new(WITHDRAW_SELECTOR)).push_arg(given_rewards),
-                )
-                .returns::<Result<(), RaffleError>>()
-                .invoke()
-                .or(Err(RaffleError::CrossContractCallError1))?;
+            // save the snapshots of the participants of the next eras
+            for (era, snapshot_root) in snapshot_commitments {
+                self.inner_commit_snapshot(pool_id, era, snapshot_root);
+            }
 
-            // set the list of winners and fund the rewards
-            let reward_manager_address = self
-                .reward_manager_address
-                .ok_or(RaffleError::RewardManagerAddressMissing)?;
+            Ok(())
+        }
 
-            debug_message("call reward manager contract");
-            ink::env::call::build_call::<Environment>()
-                .call(reward_manager_address)
-                .call_v1()
-                .transferred_value(given_rewards)
-                .exec_input(
-                    ExecutionInput::new(Selector::new(FUND_REWARDS_AND_WINNERS_SELECTOR))
-                        .push_arg(response.era)
-                        .push_arg(winners_rewards),
-                )
-                .returns::<Result<(), RaffleError>>()
-                .invoke()
-                .or(Err(RaffleError::CrossContractCallError2))?;
+        /// Handle the responses sent by the attestor,
+        /// once the rollup client has checked the conditions and applied the updates
+        fn handle_responses(
+            &mut self,
+            attestor: AccountId,
+            responses: Vec<Vec<u8>>,
+        ) -> Result<(), RollupClientError> {
+            for response in responses {
+                self.handle_response(attestor, response)?;
+            }
+            Ok(())
+        }
+    }
 
-            // emit event RaffleDone
-            self.env().emit_event(RaffleDone {
-                contract: self.env().caller(),
-                era: response.era,
-                nb_winners: u16::try_from(nb_winners)?,
-                pending_rewards: response.rewards,
-            });
+    /// Return the responses among the actions
+    fn get_responses(actions: &[HandleActionInput]) -> Vec<Vec<u8>> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                HandleActionInput::Reply(response) => Some(response.clone()),
+                _ => None,
+            })
+            .collect()
+    }
 
+    /// The responses are handled in 'rollup_cond_eq' and 'meta_tx_rollup_cond_eq'
+    /// because the quorum needs the attestor who sent them
+    impl BaseRollupClient for Contract {
+        fn on_message_received(&mut self, _action: Vec<u8>) -> Result<(), RollupClientError> {
             Ok(())
         }
+    }
+
+    /// Boilerplate code to manage the Raffle
+    impl RaffleStorage for Contract {
+        fn get_storage(&self) -> &RaffleData {
+            &self.raffle
+        }
+
+        fn get_mut_storage(&mut self) -> &mut RaffleData {
+            &mut self.raffle
+        }
+    }
+
+    impl BaseRaffle for Contract {
+        /// The last winners of the pool cannot win again
+        fn is_excluded_winner(&self, pool_id: PoolId, account: &AccountId) -> bool {
+            self.inner_get_last_winners(pool_id).contains(account)
+        }
+    }
+
+    impl Raffle for Contract {
 
         #[ink(message)]
-        pub fn set_dapps_staking_developer_address(
+        fn set_ratio_distribution(
             &mut self,
-            address: AccountId,
+            ratio: Vec<Balance>,
+            total_ratio: Balance,
         ) -> Result<(), RaffleError> {
-            only_role!(self, ADMIN_ROLE);
-            self.dapps_staking_developer_address = Some(address);
-            Ok(())
+            self.inner_set_ratio_distribution(DEFAULT_POOL, ratio, total_ratio)
         }
 
         #[ink(message)]
-        pub fn get_dapps_staking_developer_address(&mut self) -> Option<AccountId> {
-            self.dapps_staking_developer_address
+        fn get_ratio_distribution(&self) -> Vec<Balance> {
+            self.inner_get_ratio_distribution(DEFAULT_POOL)
         }
 
         #[ink(message)]
-        pub fn set_reward_manager_address(
-            &mut self,
-            address: AccountId,
-        ) -> Result<(), RaffleError> {
-            only_role!(self, ADMIN_ROLE);
-            self.reward_manager_address = Some(address);
-            Ok(())
+        fn get_total_ratio_distribution(&self) -> Balance {
+            self.inner_get_total_ratio_distribution(DEFAULT_POOL)
         }
 
         #[ink(message)]
-        pub fn get_reward_manager_address(&mut self) -> Option<AccountId> {
-            self.reward_manager_address
+        fn get_next_era(&self) -> Result<u32, RaffleError> {
+            self.inner_get_next_era(DEFAULT_POOL)
         }
 
         #[ink(message)]
-        pub fn register_attestor(
-            &mut self,
-            account_id: AccountId,
-        ) -> Result<(), AccessControlError> {
-            only_role!(self, ADMIN_ROLE);
-            AccessControl::grant_role(self, ATTESTOR_ROLE, account_id)?;
+        fn set_next_era(&mut self, next_era: u32) -> Result<(), RaffleError> {
+            self.inner_set_next_era(DEFAULT_POOL, next_era)?;
+            // the winners in cooldown for the new next era are excluded
+            self.update_winners_in_cooldown(DEFAULT_POOL, next_era);
             Ok(())
         }
 
         #[ink(message)]
-        pub fn get_attestor_role(&self) -> RoleType {
-            ATTESTOR_ROLE
+        fn set_jackpot_config(
+            &mut self,
+            enabled: bool,
+            release_ratio: u16,
+        ) -> Result<(), RaffleError> {
+            self.inner_set_jackpot_config(DEFAULT_POOL, enabled, release_ratio)
         }
 
         #[ink(message)]
-        pub fn terminate_me(&mut self) -> Result<(), RaffleError> {
-            only_role!(self, ADMIN_ROLE);
-            self.env().terminate_contract(self.env().caller());
+        fn get_jackpot_config(&self) -> (bool, u16) {
+            self.inner_get_jackpot_config(DEFAULT_POOL)
         }
 
         #[ink(message)]
-        pub fn withdraw(&mut self, value: Balance) -> Result<(), RaffleError> {
-            only_role!(self, ADMIN_ROLE);
-            let caller = Self::env().caller();
-            self.env()
-                .transfer(caller, value)
-                .map_err(|_| RaffleError::TransferError)?;
-            Ok(())
+        fn get_jackpot(&self) -> Balance {
+            self.inner_get_jackpot(DEFAULT_POOL)
         }
 
-    }
+        #[ink(message)]
+        fn set_winners_proof_required(&mut self, required: bool) -> Result<(), RaffleError> {
+            self.inner_set_winners_proof_required(required)
+        }
 
-    #[ink::scale_derive(Encode, Decode)]
-    pub struct RaffleResponseMessage {
-        pub era: u32,
-        pub skipped: bool,
-        pub rewards: Balance,
-        pub winners: Vec<AccountId>,
-    }
+        #[ink(message)]
+        fn is_winners_proof_required(&self) -> bool {
+            self.inner_is_winners_proof_required()
+        }
 
-    /// Implement the business logic for the Rollup Client in the 'on_message_received' method
-    impl BaseRollupClient for Contract {
-        fn on_message_received(&mut self, action: Vec<u8>) -> Result<(), RollupClientError> {
+        #[ink(message)]
+        fn set_remainder_destination(
+            &mut self,
+            destination: RemainderDestination,
+        ) -> Result<(), RaffleError> {
+            self.inner_set_remainder_destination(DEFAULT_POOL, destination)
+        }
 
-            // parse the response
-            let response: RaffleResponseMessage = ink::scale::Decode::decode(&mut &action[..])
-                .or(Err(RollupClientError::FailedToDecode))?;
+        #[ink(message)]
+        fn get_remainder_destination(&self) -> RemainderDestination {
+            self.inner_get_remainder_destination(DEFAULT_POOL)
+        }
 
-            self.save_response(&response)?;
+        #[ink(message)]
+        fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<(), RaffleError> {
+            self.inner_set_protocol_fee(DEFAULT_POOL, protocol_fee)
+        }
 
-            Ok(())
+        #[ink(message)]
+        fn get_protocol_fee(&self) -> u16 {
+            self.inner_get_protocol_fee(DEFAULT_POOL)
         }
-    }
 
-    /// Boilerplate code to manage the Raffle
-    impl RaffleStorage for Contract {
-        fn get_storage(&self) -> &RaffleData {
-            &self.raffle
+        #[ink(message)]
+        fn get_raffle_result(&self, era: u32) -> Option<RaffleRecord> {
+            self.inner_get_raffle_result(DEFAULT_POOL, era)
         }
 
-        fn get_mut_storage(&mut self) -> &mut RaffleData {
-            &mut self.raffle
+        #[ink(message)]
+        fn get_raffle_results(&self, from_era: u32, limit: u32) -> Vec<RaffleRecord> {
+            self.inner_get_raffle_results(DEFAULT_POOL, from_era, limit)
         }
-    }
 
-    impl BaseRaffle for Contract {}
+        #[ink(message)]
+        fn get_skip_reason(&self, era: u32) -> Option<SkipReason> {
+            self.inner_get_skip_reason(DEFAULT_POOL, era)
+        }
 
-    impl Raffle for Contract {
+        #[ink(message)]
+        fn skip_eras(
+            &mut self,
+            from_era: u32,
+            to_era: u32,
+            reason: SkipReason,
+        ) -> Result<(), RaffleError> {
+            self.inner_skip_eras(DEFAULT_POOL, from_era, to_era, reason)?;
+            // the winners in cooldown can participate again
+            self.update_winners_in_cooldown(
+                DEFAULT_POOL,
+                to_era.checked_add(1).ok_or(RaffleError::AddOverFlow)?,
+            );
+            Ok(())
+        }
 
         #[ink(message)]
-        fn set_ratio_distribution(
+        fn preview_distribution(
+            &self,
+            total_rewards: Balance,
+            winners: Vec<AccountId>,
+        ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError> {
+            self.inner_preview_distribution(DEFAULT_POOL, total_rewards, winners)
+        }
+
+        #[ink(message)]
+        fn set_pool_ratio_distribution(
             &mut self,
+            pool_id: PoolId,
             ratio: Vec<Balance>,
             total_ratio: Balance,
         ) -> Result<(), RaffleError> {
-            self.inner_set_ratio_distribution(ratio, total_ratio)
+            self.check_pool(pool_id)?;
+            self.inner_set_ratio_distribution(pool_id, ratio, total_ratio)
         }
 
         #[ink(message)]
-        fn get_ratio_distribution(&self) -> Vec<Balance> {
-            self.inner_get_ratio_distribution()
+        fn get_pool_ratio_distribution(&self, pool_id: PoolId) -> (Vec<Balance>, Balance) {
+            self.inner_get_pool_ratio_distribution(pool_id)
         }
 
         #[ink(message)]
-        fn get_total_ratio_distribution(&self) -> Balance {
-            self.inner_get_total_ratio_distribution()
+        fn get_pool_next_era(&self, pool_id: PoolId) -> Result<u32, RaffleError> {
+            self.inner_get_next_era(pool_id)
         }
 
         #[ink(message)]
-        fn get_next_era(&self) -> Result<u32, RaffleError> {
-            self.inner_get_next_era()
+        fn set_pool_next_era(&mut self, pool_id: PoolId, next_era: u32) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_set_next_era(pool_id, next_era)?;
+            // the winners in cooldown for the new next era are excluded
+            self.update_winners_in_cooldown(pool_id, next_era);
+            Ok(())
         }
 
         #[ink(message)]
-        fn set_next_era(&mut self, next_era: u32) -> Result<(), RaffleError> {
-            self.inner_set_next_era(next_era)
+        fn get_pool_jackpot(&self, pool_id: PoolId) -> Balance {
+            self.inner_get_jackpot(pool_id)
+        }
+
+        #[ink(message)]
+        fn get_pool_raffle_result(&self, pool_id: PoolId, era: u32) -> Option<RaffleRecord> {
+            self.inner_get_raffle_result(pool_id, era)
+        }
+
+        #[ink(message)]
+        fn set_pool_jackpot_config(
+            &mut self,
+            pool_id: PoolId,
+            enabled: bool,
+            release_ratio: u16,
+        ) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_set_jackpot_config(pool_id, enabled, release_ratio)
+        }
+
+        #[ink(message)]
+        fn get_pool_jackpot_config(&self, pool_id: PoolId) -> (bool, u16) {
+            self.inner_get_jackpot_config(pool_id)
+        }
+
+        #[ink(message)]
+        fn set_pool_protocol_fee(&mut self, pool_id: PoolId, protocol_fee: u16) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_set_protocol_fee(pool_id, protocol_fee)
+        }
+
+        #[ink(message)]
+        fn get_pool_protocol_fee(&self, pool_id: PoolId) -> u16 {
+            self.inner_get_protocol_fee(pool_id)
+        }
+
+        #[ink(message)]
+        fn set_pool_remainder_destination(
+            &mut self,
+            pool_id: PoolId,
+            destination: RemainderDestination,
+        ) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_set_remainder_destination(pool_id, destination)
+        }
+
+        #[ink(message)]
+        fn get_pool_remainder_destination(&self, pool_id: PoolId) -> RemainderDestination {
+            self.inner_get_remainder_destination(pool_id)
+        }
+
+        #[ink(message)]
+        fn preview_pool_distribution(
+            &self,
+            pool_id: PoolId,
+            total_rewards: Balance,
+            winners: Vec<AccountId>,
+        ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_preview_distribution(pool_id, total_rewards, winners)
         }
     }
 
@@ -293,17 +1838,17 @@ pub mod raffle_consumer {
             &mut self,
             nb_filtered_winners: u16,
         ) -> Result<(), RaffleError> {
-            self.inner_set_nb_winners_filtered(nb_filtered_winners)
+            self.inner_set_nb_winners_filtered(DEFAULT_POOL, nb_filtered_winners)
         }
 
         #[ink(message)]
         fn get_nb_winners_filtered(&self) -> u16 {
-            self.inner_get_nb_winners_filtered()
+            self.inner_get_nb_winners_filtered(DEFAULT_POOL)
         }
 
         #[ink(message)]
         fn get_last_winners(&self) -> Vec<AccountId> {
-            self.inner_get_last_winners()
+            self.inner_get_last_winners(DEFAULT_POOL)
         }
 
         #[ink(message)]
@@ -311,10 +1856,115 @@ pub mod raffle_consumer {
             &mut self,
             winner: AccountId,
         ) -> Result<(), RaffleError> {
-            self.inner_add_address_in_last_winner(winner)
+            // the address is considered as a winner of the last raffle
+            let era = self.inner_get_next_era(DEFAULT_POOL)?.saturating_sub(1);
+            self.inner_add_address_in_last_winner(DEFAULT_POOL, winner, era)
+        }
+
+        #[ink(message)]
+        fn set_cooldown_eras(&mut self, cooldown_eras: u32) -> Result<(), RaffleError> {
+            self.set_pool_cooldown_eras(DEFAULT_POOL, cooldown_eras)
+        }
+
+        #[ink(message)]
+        fn get_cooldown_eras(&self) -> u32 {
+            self.inner_get_cooldown_eras(DEFAULT_POOL)
+        }
+
+        #[ink(message)]
+        fn is_in_cooldown(&self, account: AccountId, era: u32) -> bool {
+            self.inner_is_in_cooldown(DEFAULT_POOL, account, era)
+        }
+
+        #[ink(message)]
+        fn get_pool_last_winners(&self, pool_id: PoolId) -> Vec<AccountId> {
+            self.inner_get_last_winners(pool_id)
+        }
+
+        #[ink(message)]
+        fn set_pool_nb_winners_filtered(
+            &mut self,
+            pool_id: PoolId,
+            nb_filtered_winners: u16,
+        ) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            self.inner_set_nb_winners_filtered(pool_id, nb_filtered_winners)
+        }
+
+        #[ink(message)]
+        fn get_pool_nb_winners_filtered(&self, pool_id: PoolId) -> u16 {
+            self.inner_get_nb_winners_filtered(pool_id)
+        }
+
+        #[ink(message)]
+        fn set_pool_cooldown_eras(&mut self, pool_id: PoolId, cooldown_eras: u32) -> Result<(), RaffleError> {
+            self.check_pool(pool_id)?;
+            // the excluded addresses of the pool are kept when switching the mode
+            let next_era = self.inner_get_next_era(pool_id).unwrap_or_default();
+            self.inner_set_cooldown_eras(pool_id, cooldown_eras, next_era)
+        }
+
+        #[ink(message)]
+        fn get_pool_cooldown_eras(&self, pool_id: PoolId) -> u32 {
+            self.inner_get_cooldown_eras(pool_id)
+        }
+    }
+
+
+    /// Boilerplate code to implement the timelock
+    impl TimelockStorage for Contract {
+        fn get_storage(&self) -> &TimelockData {
+            &self.timelock
+        }
+
+        fn get_mut_storage(&mut self) -> &mut TimelockData {
+            &mut self.timelock
+        }
+    }
+
+    impl BaseTimelock for Contract {}
+
+    impl Timelock for Contract {
+        #[ink(message)]
+        fn get_min_delay(&self) -> u32 {
+            self.inner_get_min_delay()
+        }
+
+        #[ink(message)]
+        fn get_operation_ready_at(&self, id: OperationId) -> Option<u32> {
+            self.inner_get_operation_ready_at(id)
+        }
+    }
+
+    /// Boilerplate code to implement the pausable component
+    impl PausableStorage for Contract {
+        fn get_storage(&self) -> &PausableData {
+            &self.pausable
+        }
+
+        fn get_mut_storage(&mut self) -> &mut PausableData {
+            &mut self.pausable
         }
     }
 
+    impl BasePausable for Contract {}
+
+    impl Pausable for Contract {
+        #[ink(message)]
+        fn pause(&mut self) -> Result<(), PausableError> {
+            self.inner_pause()
+        }
+
+        #[ink(message)]
+        fn unpause(&mut self) -> Result<(), PausableError> {
+            self.inner_unpause()
+        }
+
+        #[ink(message)]
+        fn is_paused(&self) -> bool {
+            self.inner_is_paused()
+        }
+    }
 
     /// Boilerplate code to implement the access control
     impl AccessControlStorage for Contract {
@@ -341,15 +1991,35 @@ pub mod raffle_consumer {
             role: RoleType,
             account: AccountId,
         ) -> Result<(), AccessControlError> {
-            self.inner_grant_role(role, account)
+            self.inner_grant_role(role, account)?;
+            // the attestors granted without evidence are rejected after the default validity
+            if role == ATTESTOR_ROLE {
+                let registered_at = self.env().block_number();
+                self.save_attestor_info(
+                    account,
+                    Hash::default(),
+                    Hash::default(),
+                    registered_at,
+                    registered_at.saturating_add(DEFAULT_ATTESTOR_VALIDITY),
+                );
+            }
+            Ok(())
         }
 
+        /// The attestors are removed from the registry, use `revoke_attestor` to get the detailed error
         #[ink(message)]
         fn revoke_role(
             &mut self,
             role: RoleType,
             account: AccountId,
         ) -> Result<(), AccessControlError> {
+            if role == ATTESTOR_ROLE {
+                return self.inner_revoke_attestor(account).map_err(|error| match error {
+                    RaffleError::AccessControlError(e) => e,
+                    // the quorum would not be reachable without this attestor
+                    _ => AccessControlError::InvalidCaller,
+                });
+            }
             self.inner_revoke_role(role, account)
         }
 
@@ -394,7 +2064,11 @@ pub mod raffle_consumer {
             updates: Vec<(Key, Option<Value>)>,
             actions: Vec<HandleActionInput>,
         ) -> Result<(), RollupClientError> {
-            self.inner_rollup_cond_eq(conditions, updates, actions)
+            let attestor = self.env().caller();
+            self.check_attestor_not_expired(attestor)?;
+            let responses = get_responses(&actions);
+            self.inner_rollup_cond_eq(conditions, updates, actions)?;
+            self.handle_responses(attestor, responses)
         }
     }
 
@@ -427,7 +2101,14 @@ pub mod raffle_consumer {
             request: ForwardRequest,
             signature: [u8; 65],
         ) -> Result<(), RollupClientError> {
-            self.inner_meta_tx_rollup_cond_eq(request, signature)
+            // the attestor is the signer of the request, not the caller
+            let attestor = request.from;
+            self.check_attestor_not_expired(attestor)?;
+            let (_, _, actions) = RollupCondEqMethodParams::decode(&mut request.data.as_slice())
+                .map_err(|_| RollupClientError::FailedToDecode)?;
+            let responses = get_responses(&actions);
+            self.inner_meta_tx_rollup_cond_eq(request, signature)?;
+            self.handle_responses(attestor, responses)
         }
     }
 
@@ -6,7 +6,14 @@ pub mod reward_manager {
     use inkv5_client_lib::only_role;
     use inkv5_client_lib::traits::access_control::*;
     use lucky::traits::reward::{*, psp22_reward::*};
+    use lucky::traits::pausable::*;
+    use lucky::traits::timelock::*;
     use ink::prelude::vec::Vec;
+    use ink::storage::Lazy;
+
+    /// Version of the storage layout.
+    /// When the layout changes, bump the version and add the migration step in `migrate`.
+    const STORAGE_VERSION: u16 = 1;
 
     const WHITELISTED_ADDRESS: RoleType = ink::selector_id!("WHITELISTED_ADDRESS");
 
@@ -19,6 +26,10 @@ pub mod reward_manager {
         AccessControlError(AccessControlError),
         UpgradeError,
         TransferError,
+        InsufficientSurplus,
+        PendingRewards,
+        AlreadyMigrated,
+        TimelockError(TimelockError),
     }
 
     /// convertor from RewardError to ContractError
@@ -35,17 +46,42 @@ pub mod reward_manager {
         }
     }
 
+    /// convertor from TimelockError to ContractError
+    impl From<TimelockError> for ContractError {
+        fn from(error: TimelockError) -> Self {
+            ContractError::TimelockError(error)
+        }
+    }
+
+    /// Operations specific to this contract executed after the timelock delay
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum RewardOperation {
+        /// transfer the value to the account in the currency of the rewards, only the surplus can be withdrawn
+        Withdraw(AccountId, Balance),
+    }
+
+    /// Operations executed after the timelock delay
+    pub type Operation = lucky::traits::timelock::Operation<RewardOperation>;
+
     /// Contract storage
     #[derive(Default)]
     #[ink(storage)]
     pub struct Contract {
         reward: Psp22RewardData,
         access_control: AccessControlData,
+        /// version of the storage layout, None before the versioning was introduced
+        storage_version: Lazy<u16>,
+        timelock: TimelockData,
+        pausable: PausableData,
+        /// if true, the claims are also blocked when the contract is paused
+        pause_claims: Lazy<bool>,
     }
 
     impl Contract {
+        /// The min delay is the number of blocks between the schedule and the execution of an operation
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new(min_delay: u32) -> Self {
             let mut instance = Self::default();
             let caller = instance.env().caller();
             BaseAccessControl::init_with_admin(&mut instance, caller);
@@ -53,22 +89,96 @@ pub mod reward_manager {
                 .expect("Should grant the role REWARD_MANAGER_ROLE");
             BaseAccessControl::inner_grant_role(&mut instance, REWARD_VIEWER_ROLE, caller)
                 .expect("Should grant the role REWARD_VIEWER_ROLE");
+            BaseAccessControl::inner_grant_role(&mut instance, GUARDIAN_ROLE, caller)
+                .expect("Should grant the role GUARDIAN_ROLE");
+            BaseTimelock::inner_set_min_delay(&mut instance, min_delay)
+                .expect("Should set the min delay");
+            instance.storage_version.set(&STORAGE_VERSION);
             instance
         }
 
+        /// Migrate the storage written by the previous code to the current layout.
+        /// The sum of the rewards pending before the upgrade must be given because
+        /// the rewards cannot be iterated on-chain: it is computed off-chain from the events `PendingReward` and `RewardsClaimed`
         #[ink(message)]
-        pub fn upgrade_contract(&mut self, new_code_hash: Hash) -> Result<(), ContractError> {
+        pub fn migrate(&mut self, legacy_total_pending: Balance) -> Result<(), ContractError> {
             only_role!(self, ADMIN_ROLE);
-            self.env()
-                .set_code_hash(&new_code_hash)
-                .map_err(|_| ContractError::UpgradeError)?;
+
+            let from_version = self.get_storage_version();
+            if from_version >= STORAGE_VERSION {
+                return Err(ContractError::AlreadyMigrated);
+            }
+
+            if from_version < 1 {
+                // the rewards funded before the upgrade are owed to the winners
+                self.add_total_pending(legacy_total_pending)?;
+                // the admin can pause the contract
+                let caller = self.env().caller();
+                if !self.inner_has_role(GUARDIAN_ROLE, caller) {
+                    self.inner_grant_role(GUARDIAN_ROLE, caller)?;
+                }
+            }
+
+            self.storage_version.set(&STORAGE_VERSION);
             Ok(())
         }
 
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u16 {
+            self.storage_version.get().unwrap_or_default()
+        }
+
+        /// Schedule the operation, it can be executed by anyone after the min delay
+        #[ink(message)]
+        pub fn schedule_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            self.inner_schedule_operation(&operation)?;
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            self.inner_cancel_operation(&operation)?;
+            Ok(())
+        }
+
+        /// Execute the operation when the min delay is over
+        #[ink(message)]
+        pub fn execute_operation(&mut self, operation: Operation) -> Result<(), ContractError> {
+            if let Some(operation) = self.inner_execute_operation(operation)? {
+                self.execute_reward_operation(operation)?;
+            }
+            Ok(())
+        }
+
+        fn execute_reward_operation(&mut self, operation: RewardOperation) -> Result<(), ContractError> {
+            match operation {
+                RewardOperation::Withdraw(to, value) => {
+                    // the rewards owed to the winners cannot be withdrawn
+                    let solvency = self.inner_get_solvency()?;
+                    if value > solvency.surplus {
+                        return Err(ContractError::InsufficientSurplus);
+                    }
+                    self.transfer_rewards(to, value)?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Deprecated: use the operation `UpgradeContract`.
+        /// The first call schedules the operation, the call after the min delay executes it
+        #[ink(message)]
+        pub fn upgrade_contract(&mut self, new_code_hash: Hash) -> Result<(), ContractError> {
+            self.inner_schedule_or_execute_operation(Operation::UpgradeContract(new_code_hash))?;
+            Ok(())
+        }
+
+        /// Deprecated: use the operation `TerminateContract`.
+        /// The first call schedules the operation, the call after the min delay executes it
         #[ink(message)]
         pub fn terminate_me(&mut self) -> Result<(), ContractError> {
-            only_role!(self, ADMIN_ROLE);
-            self.env().terminate_contract(self.env().caller());
+            let caller = self.env().caller();
+            self.inner_schedule_or_execute_operation(Operation::TerminateContract(caller))?;
+            Ok(())
         }
 
         #[ink(message)]
@@ -81,13 +191,93 @@ pub mod reward_manager {
             REWARD_VIEWER_ROLE
         }
 
+        #[ink(message)]
+        pub fn get_role_whitelisted_address(&self) -> RoleType {
+            WHITELISTED_ADDRESS
+        }
+
+        /// Set the PSP22 token used for the rewards, None to use the native currency
+        /// The token cannot be changed while rewards are pending
+        #[ink(message)]
+        pub fn set_reward_token(&mut self, token: Option<AccountId>) -> Result<(), ContractError> {
+            only_role!(self, ADMIN_ROLE);
+            if self.inner_get_total_pending() > 0 {
+                return Err(ContractError::PendingRewards);
+            }
+            self.inner_set_reward_token(token);
+            Ok(())
+        }
+
+        /// Set the number of eras the winners have to claim the rewards of an era, zero to disable the expiry.
+        /// The eras are counted from the last era funded by a raffle, and the rewards pending before the upgrade never expire
+        #[ink(message)]
+        pub fn set_claim_window_eras(&mut self, claim_window_eras: u32) -> Result<(), ContractError> {
+            only_role!(self, ADMIN_ROLE);
+            self.inner_set_claim_window_eras(claim_window_eras);
+            Ok(())
+        }
+
+        /// Set the destination of the rewards not claimed in time (ie the treasury or the raffle pot)
+        #[ink(message)]
+        pub fn set_expired_rewards_destination(
+            &mut self,
+            destination: Option<AccountId>,
+        ) -> Result<(), ContractError> {
+            only_role!(self, ADMIN_ROLE);
+            self.inner_set_expired_rewards_destination(destination);
+            Ok(())
+        }
+
+        /// Set if the claims are blocked when the contract is paused.
+        /// The funding of the rewards is always blocked when the contract is paused
+        #[ink(message)]
+        pub fn set_pause_claims(&mut self, pause_claims: bool) -> Result<(), ContractError> {
+            only_role!(self, ADMIN_ROLE);
+            self.pause_claims.set(&pause_claims);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_pause_claims(&self) -> bool {
+            self.pause_claims.get().unwrap_or_default()
+        }
+
+        /// return the balance of the contract, the sum of the pending rewards and the surplus
+        #[ink(message)]
+        pub fn get_solvency(&self) -> Result<Solvency, ContractError> {
+            Ok(self.inner_get_solvency()?)
+        }
+
+        /// Withdraw the given value in the currency of the rewards (native currency or PSP22 token).
+        /// An account granted with the role `WHITELISTED_ADDRESS` withdraws directly from the surplus.
+        /// Deprecated for the admin: use the operation `Withdraw`.
+        /// The first call schedules the operation, the call after the min delay executes it
         #[ink(message)]
         pub fn withdraw(&mut self, value: Balance) -> Result<(), ContractError> {
-            only_role!(self, WHITELISTED_ADDRESS);
-            let caller = Self::env().caller();
-            self.env()
-                .transfer(caller, value)
-                .map_err(|_| ContractError::TransferError)?;
+            let caller = self.env().caller();
+            if self.has_role(WHITELISTED_ADDRESS, caller) {
+                return self.execute_reward_operation(RewardOperation::Withdraw(caller, value));
+            }
+            let operation = Operation::Contract(RewardOperation::Withdraw(caller, value));
+            if let Some(operation) = self.inner_schedule_or_execute_operation(operation)? {
+                self.execute_reward_operation(operation)?;
+            }
+            Ok(())
+        }
+
+        /// An account can read its own data, the other accounts must be granted as reward viewer
+        fn check_reward_viewer(&self, account: AccountId) -> Result<(), RewardError> {
+            let caller = self.env().caller();
+            if caller != account {
+                self.inner_check_role(REWARD_VIEWER_ROLE, caller)?;
+            }
+            Ok(())
+        }
+
+        fn check_claims_not_paused(&self) -> Result<(), RewardError> {
+            if self.inner_is_paused() && self.get_pause_claims() {
+                return Err(RewardError::Paused);
+            }
             Ok(())
         }
     }
@@ -115,9 +305,25 @@ pub mod reward_manager {
             era: u32,
             accounts: Vec<(AccountId, Balance)>,
         ) -> Result<(), RewardError> {
+            if self.inner_is_paused() {
+                return Err(RewardError::Paused);
+            }
             self.inner_fund_rewards_and_add_winners(era, accounts)
         }
 
+        /// Add the winners of several eras and fund their rewards
+        /// rewards contains the era, the winner and the rewards of the winner for this era
+        #[ink(message, payable, selector = 0xef51d0aa)]
+        fn fund_rewards_by_era(
+            &mut self,
+            rewards: Vec<(u32, AccountId, Balance)>,
+        ) -> Result<(), RewardError> {
+            if self.inner_is_paused() {
+                return Err(RewardError::Paused);
+            }
+            self.inner_fund_rewards_by_era(rewards)
+        }
+
         /// return true if the current account has pending rewards
         #[ink(message)]
         fn has_pending_rewards(&self) -> bool {
@@ -145,17 +351,152 @@ pub mod reward_manager {
         /// After claiming, there is not anymore pending rewards for this account
         #[ink(message)]
         fn claim(&mut self) -> Result<(), RewardError> {
+            self.check_claims_not_paused()?;
             let from = Self::env().caller();
             self.inner_claim_from(from)
         }
 
         /// claim all pending rewards for the given account
+        /// The rewards are sent to the payout address of the account if it is set
         /// After claiming, there is not anymore pending rewards for this account
         #[ink(message)]
         fn claim_from(&mut self, from: AccountId) -> Result<(), RewardError> {
+            self.check_claims_not_paused()?;
             self.inner_claim_from(from)
         }
 
+        /// claim all pending rewards for the given account and send them to its payout address
+        /// Only the account itself or its claim operator can call it
+        #[ink(message)]
+        fn claim_for(&mut self, from: AccountId) -> Result<(), RewardError> {
+            self.check_claims_not_paused()?;
+            self.inner_claim_for(from)
+        }
+
+        /// set the address receiving the rewards of the current account
+        #[ink(message)]
+        fn set_payout_address(&mut self, payout_address: Option<AccountId>) -> Result<(), RewardError> {
+            self.inner_set_payout_address(payout_address)
+        }
+
+        #[ink(message)]
+        fn get_payout_address(&self, account: AccountId) -> Option<AccountId> {
+            self.inner_get_payout_address(account)
+        }
+
+        /// authorize the operator to claim the rewards of the current account
+        #[ink(message)]
+        fn set_claim_operator(&mut self, operator: Option<AccountId>) -> Result<(), RewardError> {
+            self.inner_set_claim_operator(operator)
+        }
+
+        #[ink(message)]
+        fn get_claim_operator(&self, account: AccountId) -> Option<AccountId> {
+            self.inner_get_claim_operator(account)
+        }
+
+        /// return the PSP22 token used for the rewards, None if the rewards are paid in native currency
+        #[ink(message)]
+        fn get_reward_token(&self) -> Option<AccountId> {
+            self.inner_get_reward_token()
+        }
+
+        /// return the number of eras the winners have to claim the rewards of an era (zero if no expiry)
+        #[ink(message)]
+        fn get_claim_window_eras(&self) -> u32 {
+            self.inner_get_claim_window_eras()
+        }
+
+        /// return the destination of the rewards not claimed in time
+        #[ink(message)]
+        fn get_expired_rewards_destination(&self) -> Option<AccountId> {
+            self.inner_get_expired_rewards_destination()
+        }
+
+        /// send the expired rewards of the given accounts to the configured destination
+        /// The accounts without expired rewards are ignored
+        #[ink(message)]
+        fn expire_rewards(&mut self, accounts: Vec<AccountId>) -> Result<(), RewardError> {
+            if self.inner_is_paused() {
+                return Err(RewardError::Paused);
+            }
+            self.inner_expire_rewards(accounts)
+        }
+
+        /// return the pending rewards of the given account by era
+        /// Only the account itself or a reward viewer can call it
+        #[ink(message)]
+        fn get_pending_rewards_by_era(
+            &mut self,
+            account: AccountId,
+        ) -> Result<Vec<(u32, Balance)>, RewardError> {
+            self.check_reward_viewer(account)?;
+            Ok(self.inner_get_pending_rewards_by_era(account))
+        }
+
+        /// return the last claims of the given account
+        /// Only the account itself or a reward viewer can call it
+        #[ink(message)]
+        fn get_claims(&mut self, account: AccountId) -> Result<Vec<ClaimRecord>, RewardError> {
+            self.check_reward_viewer(account)?;
+            Ok(self.inner_get_claims(account))
+        }
+
+    }
+
+    /// Boilerplate code to implement the timelock
+    impl TimelockStorage for Contract {
+        fn get_storage(&self) -> &TimelockData {
+            &self.timelock
+        }
+
+        fn get_mut_storage(&mut self) -> &mut TimelockData {
+            &mut self.timelock
+        }
+    }
+
+    impl BaseTimelock for Contract {}
+
+    impl Timelock for Contract {
+        #[ink(message)]
+        fn get_min_delay(&self) -> u32 {
+            self.inner_get_min_delay()
+        }
+
+        #[ink(message)]
+        fn get_operation_ready_at(&self, id: OperationId) -> Option<u32> {
+            self.inner_get_operation_ready_at(id)
+        }
+    }
+
+    /// Boilerplate code to implement the pausable component
+    impl PausableStorage for Contract {
+        fn get_storage(&self) -> &PausableData {
+            &self.pausable
+        }
+
+        fn get_mut_storage(&mut self) -> &mut PausableData {
+            &mut self.pausable
+        }
+    }
+
+    impl BasePausable for Contract {}
+
+    impl Pausable for Contract {
+        #[ink(message)]
+        fn pause(&mut self) -> Result<(), PausableError> {
+            self.inner_pause()
+        }
+
+        #[ink(message)]
+        fn unpause(&mut self) -> Result<(), PausableError> {
+            self.inner_unpause()
+        }
+
+        #[ink(message)]
+        fn is_paused(&self) -> bool {
+            self.inner_is_paused()
+        }
     }
 
     /// Boilerplate code to implement the access control
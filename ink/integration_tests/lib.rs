@@ -7,11 +7,20 @@ mod e2e_tests {
     use ink::scale::Encode;
     use ink::primitives::AccountId;
 
+    use lucky::traits::error::{DappsStakingDeveloperError, RaffleError};
     use lucky::traits::raffle::*;
     use lucky::traits::reward::psp22_reward::*;
     use dapps_staking_developer::{dapps_staking_developer, *};
     use reward_manager::{reward_manager};
-    use raffle_consumer::{RaffleResponseMessage, raffle_consumer};
+    use psp22_mock::psp22_mock::{self, PSP22};
+    use raffle_consumer::{
+        BoundRaffleResponse, DomainSeparator, Operation, RaffleAudit, RaffleOperation, RaffleResponse,
+        RaffleResponseMessage, RaffleResponseMessageV1, RaffleResponseMessageV2, ResponseEnvelope,
+        raffle_consumer,
+    };
+    use lucky::traits::pausable::Pausable;
+    use lucky::traits::timelock::Timelock;
+    use lucky::traits::winner_proof::*;
 
     use inkv5_client_lib::traits::access_control::*;
     use inkv5_client_lib::traits::meta_transaction::*;
@@ -19,6 +28,28 @@ mod e2e_tests {
 
     type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+    /// Check the call emitted the event of the given type with the given fields,
+    /// the fields are given in a tuple in the order of their declaration in the event
+    macro_rules! assert_event_emitted {
+        ($result:expr, $event:ty, $fields:expr) => {{
+            let signature_topic = <$event as ink::env::Event>::SIGNATURE_TOPIC;
+            let data = ink::scale::Encode::encode(&$fields);
+            let events = $result
+                .contract_emitted_events()
+                .expect("fail to decode the contract events");
+            assert!(
+                events.iter().any(|e| {
+                    e.topics.first().map(|topic| topic.0) == signature_topic && e.event.data == data
+                }),
+                "event {} not emitted",
+                stringify!($event)
+            );
+        }};
+    }
+
+    /// number of blocks between the schedule and the execution of an operation
+    const MIN_DELAY: u32 = 1;
+
     async fn alice_instantiates_reward_manager_contract<Client>(
         client: &mut Client,
     ) -> InstantiationResult<
@@ -29,7 +60,7 @@ mod e2e_tests {
         Client: E2EBackend,
         <Client as ContractsBackend<DefaultEnvironment>>::Error: Debug,
     {
-        let mut reward_manager_constructor = reward_manager::ContractRef::new();
+        let mut reward_manager_constructor = reward_manager::ContractRef::new(MIN_DELAY);
         let reward_manager_contract = client
             .instantiate(
                 "reward_manager",
@@ -54,7 +85,7 @@ mod e2e_tests {
         Client: E2EBackend,
         <Client as ContractsBackend<DefaultEnvironment>>::Error: Debug,
     {
-        let mut dapps_staking_developer_constructor = dapps_staking_developer::ContractRef::new();
+        let mut dapps_staking_developer_constructor = dapps_staking_developer::ContractRef::new(MIN_DELAY);
         let dapps_staking_developer_contract = client
             .instantiate(
                 "dapps_staking_developer",
@@ -84,6 +115,7 @@ mod e2e_tests {
         let mut raffle_consumer_constructor = raffle_consumer::ContractRef::new(
             dapps_staking_developer_account_id,
             reward_manager_account_id,
+            MIN_DELAY,
         );
         let raffle_consumer_contract = client
             .instantiate(
@@ -179,6 +211,73 @@ mod e2e_tests {
             .expect("grant bob as attestor failed");
     }
 
+    /// Alice schedules the operation and executes it once the timelock delay is over
+    async fn alice_executes_operation<Client>(
+        client: &mut Client,
+        contract: &InstantiationResult<
+            DefaultEnvironment,
+            <Client as ContractsBackend<DefaultEnvironment>>::EventLog,
+        >,
+        operation: Operation,
+    ) where
+        Client: E2EBackend,
+        <Client as ContractsBackend<DefaultEnvironment>>::Error: Debug,
+    {
+        let schedule_operation = contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(operation.clone());
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+
+        let execute_operation = contract
+            .call_builder::<raffle_consumer::Contract>()
+            .execute_operation(operation);
+        client
+            .call(&ink_e2e::alice(), &execute_operation)
+            .submit()
+            .await
+            .expect("execute operation failed");
+    }
+
+    /// Encode the results of the default pool in the envelope sent by the worker
+    fn encode_response(responses: Vec<RaffleResponseMessage>) -> Vec<u8> {
+        let responses = responses
+            .into_iter()
+            .map(|response| RaffleResponseMessageV2 {
+                response,
+                audit: None,
+                next_random_commitment: None,
+                next_snapshot_commitment: None,
+            })
+            .collect();
+        ResponseEnvelope::Unbound(RaffleResponse::V2 { pool_id: None, responses }).encode_action()
+    }
+
+    async fn bob_sends_response<Client>(
+        client: &mut Client,
+        contract: &InstantiationResult<
+            DefaultEnvironment,
+            <Client as ContractsBackend<DefaultEnvironment>>::EventLog,
+        >,
+        response: RaffleResponseMessage,
+    ) where
+        Client: E2EBackend,
+        <Client as ContractsBackend<DefaultEnvironment>>::Error: Debug,
+    {
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+    }
+
     #[ink_e2e::test]
     async fn test_do_raffle<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
         // given
@@ -223,8 +322,8 @@ mod e2e_tests {
 
         let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
 
-        // data is received
-        let response = RaffleResponseMessage {
+        // data is received in the format of the first version of the worker
+        let response = RaffleResponseMessageV1 {
             era: 13,
             skipped: false,
             rewards: 100,
@@ -257,6 +356,18 @@ mod e2e_tests {
         let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
         assert!(result.is_err(), "Era must be sequential without blank");
 
+        // the error is sent back with its stable code
+        let result = client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .dry_run()
+            .await
+            .expect("dry_run should be ok")
+            .return_value();
+        assert_eq!(
+            Err(RollupClientError::BusinessError(RaffleError::IncorrectEra.code())),
+            result
+        );
+
         // and check if the data is filled
         let get_next_era = raffle_consumer_contract
             .call_builder::<raffle_consumer::Contract>()
@@ -272,6 +383,24 @@ mod e2e_tests {
 
         assert_eq!(14, next_era);
 
+        // check the result of the raffle is saved
+        let get_raffle_result = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_raffle_result(13);
+
+        let raffle_result = client
+            .call(&ink_e2e::charlie(), &get_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get raffle result")
+            .return_value()
+            .expect("raffle result not found");
+
+        assert_eq!(13, raffle_result.era);
+        assert!(!raffle_result.skipped);
+        assert_eq!(100, raffle_result.total_rewards);
+        assert_eq!(vec![(dave_address, 10)], raffle_result.winners);
+
         // check the balance of the developer contract
         let dev_contract_balance = client
             .free_balance(dapps_staking_developer_contract.account_id)
@@ -296,6 +425,41 @@ mod e2e_tests {
 
         assert_eq!(1000000000, raffle_consumer_balance);
 
+        // check the pending rewards of dave by era
+        let get_pending_rewards_by_era = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_by_era(dave_address);
+
+        let pending_rewards_by_era = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value()
+            .expect("pending rewards by era failed");
+
+        assert_eq!(vec![(13, 10)], pending_rewards_by_era);
+
+        // dave can read his own rewards by era, but not charlie
+        let pending_rewards_by_era = client
+            .call(&ink_e2e::dave(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value()
+            .expect("pending rewards by era failed");
+
+        assert_eq!(vec![(13, 10)], pending_rewards_by_era);
+
+        let result = client
+            .call(&ink_e2e::charlie(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value();
+
+        assert!(result.is_err(), "Only the account or a reward viewer can read the rewards by era");
+
         // check the balance of dave
         let dave_balance_before_claim = client
             .free_balance(dave_address)
@@ -334,77 +498,197 @@ mod e2e_tests {
             reward_manager_contract_balance - 10
         );
 
+        // check the claim is kept in the history with the eras
+        let get_claims = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_claims(dave_address);
+
+        let claims = client
+            .call(&ink_e2e::dave(), &get_claims)
+            .dry_run()
+            .await
+            .expect("fail to get claims")
+            .return_value()
+            .expect("claims failed");
+
+        assert_eq!(1, claims.len());
+        assert_eq!(10, claims[0].amount);
+        assert_eq!(vec![(13, 10)], claims[0].eras);
+
+        // the error returned by the dAppStaking developer contract is propagated with its code
+        let revoke_whitelisted_role = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .revoke_role(WHITELISTED_ADDRESS, raffle_consumer_contract.account_id);
+        client
+            .call(&ink_e2e::alice(), &revoke_whitelisted_role)
+            .submit()
+            .await
+            .expect("revoke whitelisted role failed");
+
+        let response = RaffleResponseMessageV1 {
+            era: 14,
+            skipped: false,
+            rewards: 100,
+            winners: [dave_address].to_vec(),
+        };
+        let actions = vec![HandleActionInput::Reply(response.encode())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .dry_run()
+            .await
+            .expect("dry_run should be ok")
+            .return_value();
+        let expected_error = RaffleError::DappsStakingDeveloperError(
+            DappsStakingDeveloperError::AccessControlError(AccessControlError::MissingRole),
+        );
+        assert_eq!(2_001, expected_error.code());
+        assert_eq!(Err(RollupClientError::BusinessError(expected_error.code())), result);
+
         Ok(())
     }
 
     #[ink_e2e::test]
-    async fn test_skip_raffle<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+    async fn test_configuration_events<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
         // given
         let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
         let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let pool_dev_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
         let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
             &mut client,
             dapps_staking_developer_contract.account_id,
             reward_manager_contract.account_id,
         ).await;
 
-        // configure the contracts
-        alice_configure_contracts(
-            &mut client,
-            &reward_manager_contract,
-            &dapps_staking_developer_contract,
-            &raffle_consumer_contract
-        ).await;
-
-        // bob is granted as attestor
-        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
-
-        // data is received
-        let response = RaffleResponseMessage {
-            era: 13,
-            skipped: true,
-            rewards: 0,
-            winners: [].to_vec(),
-        };
+        let ferdie_address = ink::primitives::AccountId::from(ink_e2e::ferdie().public_key().0);
 
-        let actions = vec![HandleActionInput::Reply(response.encode())];
-        let rollup_cond_eq = raffle_consumer_contract
+        let set_ratio_distribution = raffle_consumer_contract
             .call_builder::<raffle_consumer::Contract>()
-            .rollup_cond_eq(vec![], vec![], actions.clone());
+            .set_ratio_distribution(vec![10, 5], 100);
+        let result = client
+            .call(&ink_e2e::alice(), &set_ratio_distribution)
+            .submit()
+            .await
+            .expect("set ratio distribution failed");
+        assert_event_emitted!(result, RatioDistributionUpdated, (0u32, vec![10u128, 5u128], 100u128));
 
+        let set_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_next_era(13);
         let result = client
-            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .call(&ink_e2e::alice(), &set_next_era)
             .submit()
             .await
-            .expect("rollup cond eq should be ok");
-        // two events : MessageProcessedTo and RaffleSkipped
-        assert!(result.contains_event("Contracts", "ContractEmitted"));
+            .expect("set next era failed");
+        assert_event_emitted!(result, NextEraUpdated, (0u32, 13u32));
 
-        // and check if the data is filled
-        let get_next_era = raffle_consumer_contract
+        let set_nb_winners_filtered = raffle_consumer_contract
             .call_builder::<raffle_consumer::Contract>()
-            .get_next_era();
+            .set_nb_winners_filtered(2);
+        let result = client
+            .call(&ink_e2e::alice(), &set_nb_winners_filtered)
+            .submit()
+            .await
+            .expect("set nb winners filtered failed");
+        assert_event_emitted!(
+            result,
+            lucky::traits::participant_filter::filter_latest_winners::NbWinnersFilteredUpdated,
+            (0u32, 2u16)
+        );
 
-        let next_era = client
-            .call(&ink_e2e::charlie(), &get_next_era)
-            .dry_run()
+        let set_remainder_destination = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_remainder_destination(RemainderDestination::FirstWinner);
+        let result = client
+            .call(&ink_e2e::alice(), &set_remainder_destination)
+            .submit()
             .await
-            .expect("fail to get next era")
-            .return_value()
-            .expect("next era failed");
+            .expect("set remainder destination failed");
+        assert_event_emitted!(result, RemainderDestinationUpdated, (0u32, RemainderDestination::FirstWinner));
 
-        assert_eq!(14, next_era);
+        let set_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_protocol_fee(100);
+        let result = client
+            .call(&ink_e2e::alice(), &set_protocol_fee)
+            .submit()
+            .await
+            .expect("set protocol fee failed");
+        assert_event_emitted!(result, ProtocolFeeUpdated, (0u32, 100u16));
 
-        // test wrong era => meaning only 1 raffle by era
-        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
-        assert!(result.is_err(), "Era must be sequential without blank");
+        let set_quorum_threshold = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_quorum_threshold(1);
+        let result = client
+            .call(&ink_e2e::alice(), &set_quorum_threshold)
+            .submit()
+            .await
+            .expect("set quorum threshold failed");
+        assert_event_emitted!(result, raffle_consumer::QuorumThresholdUpdated, (1u8,));
+
+        // the addresses are set by the timelocked operations
+        let operations = [
+            RaffleOperation::SetDappsStakingDeveloperAddress(ferdie_address),
+            RaffleOperation::SetRewardManagerAddress(ferdie_address),
+            RaffleOperation::SetTreasuryAddress(ferdie_address),
+            RaffleOperation::SetPoolDappsStakingDeveloperAddress(1, pool_dev_contract.account_id),
+            RaffleOperation::RemovePool(1),
+        ];
+        for operation in operations {
+            let operation = Operation::Contract(operation);
+            let schedule_operation = raffle_consumer_contract
+                .call_builder::<raffle_consumer::Contract>()
+                .schedule_operation(operation.clone());
+            client
+                .call(&ink_e2e::alice(), &schedule_operation)
+                .submit()
+                .await
+                .expect("schedule operation failed");
+
+            let execute_operation = raffle_consumer_contract
+                .call_builder::<raffle_consumer::Contract>()
+                .execute_operation(operation.clone());
+            let result = client
+                .call(&ink_e2e::alice(), &execute_operation)
+                .submit()
+                .await
+                .expect("execute operation failed");
+
+            match operation {
+                Operation::Contract(RaffleOperation::SetDappsStakingDeveloperAddress(address)) => {
+                    assert_event_emitted!(
+                        result,
+                        raffle_consumer::DappsStakingDeveloperAddressUpdated,
+                        (0u32, address)
+                    );
+                }
+                Operation::Contract(RaffleOperation::SetRewardManagerAddress(address)) => {
+                    assert_event_emitted!(result, raffle_consumer::RewardManagerAddressUpdated, (address,));
+                }
+                Operation::Contract(RaffleOperation::SetTreasuryAddress(address)) => {
+                    assert_event_emitted!(result, raffle_consumer::TreasuryAddressUpdated, (address,));
+                }
+                Operation::Contract(RaffleOperation::SetPoolDappsStakingDeveloperAddress(pool_id, address)) => {
+                    assert_event_emitted!(
+                        result,
+                        raffle_consumer::DappsStakingDeveloperAddressUpdated,
+                        (pool_id, address)
+                    );
+                }
+                Operation::Contract(RaffleOperation::RemovePool(pool_id)) => {
+                    assert_event_emitted!(result, raffle_consumer::PoolRemoved, (pool_id,));
+                }
+                _ => unreachable!(),
+            }
+        }
 
         Ok(())
     }
 
-
     #[ink_e2e::test]
-    async fn test_bad_attestor<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+    async fn test_claim_with_payout_address<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
         // given
         let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
         let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
@@ -422,34 +706,150 @@ mod e2e_tests {
             &raffle_consumer_contract
         ).await;
 
-        // bob is not granted as attestor => it should not be able to send a message
-        let rollup_cond_eq = raffle_consumer_contract
-            .call_builder::<raffle_consumer::Contract>()
-            .rollup_cond_eq(vec![], vec![], vec![]);
-        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
-        assert!(
-            result.is_err(),
-            "only attestor should be able to send messages"
-        );
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(100)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
 
         // bob is granted as attestor
         alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
 
-        // then bob is able to send a message
-        let result = client
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let eve_address = ink::primitives::AccountId::from(ink_e2e::eve().public_key().0);
+        let ferdie_address = ink::primitives::AccountId::from(ink_e2e::ferdie().public_key().0);
+
+        // dave wins the raffle of the given era
+        let response = |era: u32| RaffleResponseMessage {
+            era,
+            skip_reason: None,
+            rewards: 100,
+            winners: [dave_address].to_vec(),
+            winners_proof: None,
+        };
+
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response(13)]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
             .call(&ink_e2e::bob(), &rollup_cond_eq)
             .submit()
             .await
-            .expect("rollup cond eq failed");
-        // no event
-        assert!(!result.contains_event("Contracts", "ContractEmitted"));
+            .expect("rollup cond eq should be ok");
+
+        // charlie is not authorized to claim the rewards of dave
+        let claim_for = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .claim_for(dave_address);
+        let result = client.call(&ink_e2e::charlie(), &claim_for).submit().await;
+        assert!(result.is_err(), "Charlie is not the claim operator of Dave");
+
+        // dave sets eve as payout address and charlie as claim operator
+        let set_payout_address = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_payout_address(Some(eve_address));
+        client
+            .call(&ink_e2e::dave(), &set_payout_address)
+            .submit()
+            .await
+            .expect("set payout address failed");
+
+        let set_claim_operator = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_claim_operator(Some(charlie_address));
+        client
+            .call(&ink_e2e::dave(), &set_claim_operator)
+            .submit()
+            .await
+            .expect("set claim operator failed");
+
+        // a keeper claims the rewards of dave, they are sent to eve
+        let eve_balance_before_claim = client
+            .free_balance(eve_address)
+            .await
+            .expect("getting Eve balance failed");
+
+        let claim_from = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .claim_from(dave_address);
+        client
+            .call(&ink_e2e::ferdie(), &claim_from)
+            .submit()
+            .await
+            .expect("claim from should be ok");
+
+        let eve_balance_after_claim = client
+            .free_balance(eve_address)
+            .await
+            .expect("getting Eve balance failed");
+
+        assert_eq!(eve_balance_before_claim + 10, eve_balance_after_claim);
+
+        // dave wins the next raffle, charlie claims the rewards of dave
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response(14)]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // charlie tries to redirect the rewards to himself with his own payout address
+        let set_payout_address = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_payout_address(Some(ferdie_address));
+        client
+            .call(&ink_e2e::charlie(), &set_payout_address)
+            .submit()
+            .await
+            .expect("set payout address failed");
+
+        let eve_balance_before_claim = client
+            .free_balance(eve_address)
+            .await
+            .expect("getting Eve balance failed");
+        let ferdie_balance_before_claim = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+
+        let claim_for = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .claim_for(dave_address);
+        client
+            .call(&ink_e2e::charlie(), &claim_for)
+            .submit()
+            .await
+            .expect("claim for should be ok");
+
+        // the rewards are still sent to the payout address of dave
+        let eve_balance_after_claim = client
+            .free_balance(eve_address)
+            .await
+            .expect("getting Eve balance failed");
+        assert_eq!(eve_balance_before_claim + 10, eve_balance_after_claim);
+
+        let ferdie_balance_after_claim = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+        assert_eq!(ferdie_balance_before_claim, ferdie_balance_after_claim);
 
         Ok(())
     }
 
-
     #[ink_e2e::test]
-    async fn test_bad_messages(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+    async fn test_do_raffle_with_reward_token<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
         // given
         let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
         let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
@@ -467,10 +867,2727 @@ mod e2e_tests {
             &raffle_consumer_contract
         ).await;
 
-        // bob is granted as attestor
-        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+        // alice mints the token used for the rewards
+        let mut psp22_constructor = psp22_mock::ContractRef::new(1000);
+        let psp22_contract = client
+            .instantiate("psp22_mock", &ink_e2e::alice(), &mut psp22_constructor)
+            .submit()
+            .await
+            .expect("instantiate failed");
 
-        let actions = vec![HandleActionInput::Reply(58u128.encode())];
+        // the rewards are paid in token
+        let set_reward_token = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_reward_token(Some(psp22_contract.account_id));
+
+        client
+            .call(&ink_e2e::alice(), &set_reward_token)
+            .submit()
+            .await
+            .expect("set reward token failed");
+
+        // the same token is set in the raffle contract
+        alice_executes_operation(
+            &mut client,
+            &raffle_consumer_contract,
+            Operation::Contract(RaffleOperation::SetRewardToken(Some(psp22_contract.account_id))),
+        ).await;
+
+        let get_reward_token = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_reward_token();
+
+        let reward_token = client
+            .call(&ink_e2e::charlie(), &get_reward_token)
+            .dry_run()
+            .await
+            .expect("fail to get the reward token")
+            .return_value();
+
+        assert_eq!(Some(psp22_contract.account_id), reward_token);
+
+        // the tokens are deposited in the raffle contract
+        let transfer = psp22_contract
+            .call_builder::<psp22_mock::Contract>()
+            .transfer(raffle_consumer_contract.account_id, 100, vec![]);
+
+        client
+            .call(&ink_e2e::alice(), &transfer)
+            .submit()
+            .await
+            .expect("transfer failed")
+            .return_value()
+            .expect("transfer should be ok");
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // data is received
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 13,
+                skip_reason: None,
+                rewards: 100,
+                winners: [dave_address].to_vec(),
+                winners_proof: None,
+            },
+        ).await;
+
+        // the rewards are transferred in token from the raffle contract to the reward manager
+        let balance_of = psp22_contract
+            .call_builder::<psp22_mock::Contract>()
+            .balance_of(raffle_consumer_contract.account_id);
+
+        let raffle_consumer_token_balance = client
+            .call(&ink_e2e::charlie(), &balance_of)
+            .dry_run()
+            .await
+            .expect("fail to get the balance")
+            .return_value();
+
+        assert_eq!(90, raffle_consumer_token_balance);
+
+        let balance_of = psp22_contract
+            .call_builder::<psp22_mock::Contract>()
+            .balance_of(reward_manager_contract.account_id);
+
+        let reward_manager_token_balance = client
+            .call(&ink_e2e::charlie(), &balance_of)
+            .dry_run()
+            .await
+            .expect("fail to get the balance")
+            .return_value();
+
+        assert_eq!(10, reward_manager_token_balance);
+
+        // nothing is withdrawn from the developer contract
+        let dev_contract_balance = client
+            .free_balance(dapps_staking_developer_contract.account_id)
+            .await
+            .expect("getting dev contract balance failed");
+
+        assert_eq!(1000000000, dev_contract_balance);
+
+        // the reward manager owes the tokens to dave
+        let get_solvency = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_solvency();
+
+        let solvency = client
+            .call(&ink_e2e::charlie(), &get_solvency)
+            .dry_run()
+            .await
+            .expect("fail to get the solvency")
+            .return_value()
+            .expect("solvency failed");
+
+        assert_eq!(10, solvency.balance);
+        assert_eq!(10, solvency.total_pending);
+        assert_eq!(0, solvency.surplus);
+
+        // dave claims his rewards in token
+        let claim = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .claim();
+
+        client
+            .call(&ink_e2e::dave(), &claim)
+            .submit()
+            .await
+            .expect("Claim rewards should be ok");
+
+        let balance_of = psp22_contract
+            .call_builder::<psp22_mock::Contract>()
+            .balance_of(dave_address);
+
+        let dave_token_balance = client
+            .call(&ink_e2e::charlie(), &balance_of)
+            .dry_run()
+            .await
+            .expect("fail to get the balance")
+            .return_value();
+
+        assert_eq!(10, dave_token_balance);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_expire_rewards<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(300)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // the winners have one era to claim the rewards and the expired rewards go to ferdie
+        let ferdie_address = ink::primitives::AccountId::from(ink_e2e::ferdie().public_key().0);
+
+        let set_claim_window_eras = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_claim_window_eras(1);
+
+        client
+            .call(&ink_e2e::alice(), &set_claim_window_eras)
+            .submit()
+            .await
+            .expect("set claim window eras failed");
+
+        let set_expired_rewards_destination = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .set_expired_rewards_destination(Some(ferdie_address));
+
+        client
+            .call(&ink_e2e::alice(), &set_expired_rewards_destination)
+            .submit()
+            .await
+            .expect("set expired rewards destination failed");
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // dave wins the eras 13 and 14, charlie wins the era 15
+        for (era, winner) in [(13, dave_address), (14, dave_address), (15, charlie_address)] {
+            bob_sends_response(
+                &mut client,
+                &raffle_consumer_contract,
+                RaffleResponseMessage {
+                    era,
+                    skip_reason: None,
+                    rewards: 100,
+                    winners: [winner].to_vec(),
+                    winners_proof: None,
+                },
+            ).await;
+        }
+
+        // the rewards of the era 13 are expired but not the ones of the era 14
+        let claim = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .claim();
+
+        client
+            .call(&ink_e2e::dave(), &claim)
+            .submit()
+            .await
+            .expect("Claim rewards should be ok");
+
+        let get_claims = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_claims(dave_address);
+
+        let claims = client
+            .call(&ink_e2e::alice(), &get_claims)
+            .dry_run()
+            .await
+            .expect("fail to get claims")
+            .return_value()
+            .expect("claims failed");
+
+        assert_eq!(1, claims.len());
+        assert_eq!(10, claims[0].amount);
+        assert_eq!(vec![(14, 10)], claims[0].eras);
+
+        // only the expired rewards are still pending
+        let get_pending_rewards_by_era = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_by_era(dave_address);
+
+        let pending_rewards_by_era = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value()
+            .expect("pending rewards by era failed");
+
+        assert_eq!(vec![(13, 10)], pending_rewards_by_era);
+
+        let result = client.call(&ink_e2e::dave(), &claim).submit().await;
+        assert!(result.is_err(), "The expired rewards cannot be claimed");
+
+        // the expired rewards are sent to ferdie, the rewards of charlie are not expired
+        let ferdie_balance_before = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+
+        let expire_rewards = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .expire_rewards(vec![dave_address, charlie_address]);
+
+        // the rewards cannot be expired while the contract is paused
+        let pause = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .pause();
+        client
+            .call(&ink_e2e::alice(), &pause)
+            .submit()
+            .await
+            .expect("pause failed");
+
+        let result = client.call(&ink_e2e::alice(), &expire_rewards).submit().await;
+        assert!(result.is_err(), "The contract is paused");
+
+        let unpause = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .unpause();
+        client
+            .call(&ink_e2e::alice(), &unpause)
+            .submit()
+            .await
+            .expect("unpause failed");
+
+        client
+            .call(&ink_e2e::alice(), &expire_rewards)
+            .submit()
+            .await
+            .expect("expire rewards should be ok");
+
+        let ferdie_balance_after = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+
+        assert_eq!(ferdie_balance_before + 10, ferdie_balance_after);
+
+        let get_pending_rewards_from = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_from(dave_address);
+
+        let pending_rewards = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_from)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards")
+            .return_value()
+            .expect("pending rewards failed");
+
+        assert_eq!(None, pending_rewards);
+
+        let get_pending_rewards_from = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_from(charlie_address);
+
+        let pending_rewards = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_from)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards")
+            .return_value()
+            .expect("pending rewards failed");
+
+        assert_eq!(Some(10), pending_rewards);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_skip_raffle<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // data is received
+        let response = RaffleResponseMessageV1 {
+            era: 13,
+            skipped: true,
+            rewards: 0,
+            winners: [].to_vec(),
+        };
+
+        let actions = vec![HandleActionInput::Reply(response.encode())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions.clone());
+
+        let result = client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+        // two events : MessageProcessedTo and RaffleSkipped
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        // and check if the data is filled
+        let get_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_next_era();
+
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+
+        assert_eq!(14, next_era);
+
+        // test wrong era => meaning only 1 raffle by era
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "Era must be sequential without blank");
+
+        // check the reason is saved
+        let get_skip_reason = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_skip_reason(13);
+
+        let skip_reason = client
+            .call(&ink_e2e::charlie(), &get_skip_reason)
+            .dry_run()
+            .await
+            .expect("fail to get skip reason")
+            .return_value();
+
+        assert_eq!(Some(SkipReason::VotingSubPeriod), skip_reason);
+
+        // the admin skips the eras 14 to 20
+        let skip_eras = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .skip_eras(14, 20, SkipReason::OperatorDecision);
+
+        let result = client.call(&ink_e2e::bob(), &skip_eras).submit().await;
+        assert!(result.is_err(), "Only the admin can skip the eras");
+
+        client
+            .call(&ink_e2e::alice(), &skip_eras)
+            .submit()
+            .await
+            .expect("skip eras failed");
+
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+
+        assert_eq!(21, next_era);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_jackpot<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(200)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // the jackpot is enabled and half of it is released in each raffle
+        let set_jackpot_config = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_jackpot_config(true, 5000);
+
+        let result = client.call(&ink_e2e::bob(), &set_jackpot_config).submit().await;
+        assert!(result.is_err(), "Only the raffle manager can set the jackpot");
+
+        client
+            .call(&ink_e2e::alice(), &set_jackpot_config)
+            .submit()
+            .await
+            .expect("set jackpot config failed");
+
+        // the era 13 is skipped, its rewards are kept in the jackpot
+        let response = RaffleResponseMessage {
+            era: 13,
+            skip_reason: Some(SkipReason::NoParticipant),
+            rewards: 100,
+            winners: [].to_vec(),
+            winners_proof: None,
+        };
+
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let get_jackpot = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_jackpot();
+
+        let jackpot = client
+            .call(&ink_e2e::charlie(), &get_jackpot)
+            .dry_run()
+            .await
+            .expect("fail to get jackpot")
+            .return_value();
+
+        assert_eq!(100, jackpot);
+
+        // the jackpot is published in the kv store for the worker
+        let get_value = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_value(ink::selector_id!("JACKPOT").encode());
+
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the jackpot in the kv store")
+            .return_value();
+
+        assert_eq!(Some(100u128.encode()), value);
+
+        // the era 14 is raffled, half of the jackpot is added to the rewards of the winner
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let response = RaffleResponseMessage {
+            era: 14,
+            skip_reason: None,
+            rewards: 100,
+            winners: [dave_address].to_vec(),
+            winners_proof: None,
+        };
+
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // 10% of the rewards (10) + the released jackpot (50)
+        let get_raffle_result = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_raffle_result(14);
+
+        let raffle_result = client
+            .call(&ink_e2e::charlie(), &get_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get raffle result")
+            .return_value()
+            .expect("raffle result not found");
+
+        assert_eq!(vec![(dave_address, 60)], raffle_result.winners);
+
+        // the jackpot keeps the rewards not distributed: 100 + 100 - 60
+        let jackpot = client
+            .call(&ink_e2e::charlie(), &get_jackpot)
+            .dry_run()
+            .await
+            .expect("fail to get jackpot")
+            .return_value();
+
+        assert_eq!(140, jackpot);
+
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the jackpot in the kv store")
+            .return_value();
+
+        assert_eq!(Some(140u128.encode()), value);
+
+        // only the rewards of the winner left the developer contract
+        let dev_contract_balance = client
+            .free_balance(dapps_staking_developer_contract.account_id)
+            .await
+            .expect("getting dev contract balance failed");
+
+        assert_eq!(1000000140, dev_contract_balance);
+
+        let reward_manager_contract_balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+
+        assert_eq!(1000000060, reward_manager_contract_balance);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_cancel_raffle<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        let set_nb_winners_filtered = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_nb_winners_filtered(2);
+        client
+            .call(&ink_e2e::alice(), &set_nb_winners_filtered)
+            .submit()
+            .await
+            .expect("set nb winners filtered failed");
+
+        // the rewards wait for the end of the dispute window
+        let set_dispute_window = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_dispute_window(100);
+        client
+            .call(&ink_e2e::alice(), &set_dispute_window)
+            .submit()
+            .await
+            .expect("set dispute window failed");
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let response = |winner: AccountId| RaffleResponseMessage {
+            era: 13,
+            skip_reason: None,
+            rewards: 100,
+            winners: vec![winner],
+            winners_proof: None,
+        };
+
+        // dave wins the raffle, he is excluded from the next raffle
+        bob_sends_response(&mut client, &raffle_consumer_contract, response(dave_address)).await;
+
+        let get_last_winners = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_last_winners();
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+        assert_eq!(vec![dave_address], last_winners);
+
+        // the result is cancelled
+        let cancel_raffle = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .cancel_raffle(13, String::from("wrong snapshot"));
+        let result = client.call(&ink_e2e::bob(), &cancel_raffle).submit().await;
+        assert!(result.is_err(), "Only the raffle manager can cancel the raffle");
+
+        client
+            .call(&ink_e2e::alice(), &cancel_raffle)
+            .submit()
+            .await
+            .expect("cancel raffle failed");
+
+        // dave is no longer excluded, in the contract and in the kv store
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+        assert!(last_winners.is_empty());
+
+        let get_value = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_value(ink::selector_id!("LAST_WINNER").encode());
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the last winners in the kv store")
+            .return_value();
+        assert_eq!(Some(Vec::<AccountId>::new().encode()), value);
+
+        // the raffle is run again and charlie wins
+        bob_sends_response(&mut client, &raffle_consumer_contract, response(charlie_address)).await;
+
+        let get_pending_raffle = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pending_raffle(13);
+        let pending_raffle = client
+            .call(&ink_e2e::alice(), &get_pending_raffle)
+            .dry_run()
+            .await
+            .expect("fail to get the pending raffle")
+            .return_value()
+            .expect("pending raffle not found");
+        assert_eq!(vec![charlie_address], pending_raffle.winners);
+        assert_eq!(vec![(charlie_address, 10)], pending_raffle.winners_rewards);
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+        assert_eq!(vec![charlie_address], last_winners);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_protocol_fee<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(100)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // ferdie is the treasury
+        let ferdie_address = ink::primitives::AccountId::from(ink_e2e::ferdie().public_key().0);
+        alice_executes_operation(
+            &mut client,
+            &raffle_consumer_contract,
+            Operation::Contract(RaffleOperation::SetTreasuryAddress(ferdie_address)),
+        ).await;
+
+        // the fee cannot exceed 10%
+        let set_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_protocol_fee(MAX_PROTOCOL_FEE + 1);
+        let result = client.call(&ink_e2e::alice(), &set_protocol_fee).submit().await;
+        assert!(result.is_err(), "The protocol fee is too high");
+
+        let set_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_protocol_fee(MAX_PROTOCOL_FEE);
+        let result = client.call(&ink_e2e::bob(), &set_protocol_fee).submit().await;
+        assert!(result.is_err(), "Only the admin can set the protocol fee");
+
+        client
+            .call(&ink_e2e::alice(), &set_protocol_fee)
+            .submit()
+            .await
+            .expect("set protocol fee failed");
+
+        // the protocol fee is published in the kv store for the worker
+        let get_value = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_value(ink::selector_id!("PROTOCOL_FEE").encode());
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the protocol fee in the kv store")
+            .return_value();
+        assert_eq!(Some(MAX_PROTOCOL_FEE.encode()), value);
+
+        let ferdie_balance_before = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+
+        // dave wins 10% of the rewards (10), 10% of them (1) is taken by the treasury
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 13,
+                skip_reason: None,
+                rewards: 100,
+                winners: vec![dave_address],
+                winners_proof: None,
+            },
+        ).await;
+
+        // the result is saved after the fee
+        let get_raffle_result = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_raffle_result(13);
+        let raffle_result = client
+            .call(&ink_e2e::charlie(), &get_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get raffle result")
+            .return_value()
+            .expect("raffle result not found");
+        assert_eq!(vec![(dave_address, 9)], raffle_result.winners);
+        assert_eq!(1, raffle_result.protocol_fee);
+
+        let get_pending_rewards_from = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_from(dave_address);
+        let pending_rewards = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_from)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards")
+            .return_value()
+            .expect("pending rewards failed");
+        assert_eq!(Some(9), pending_rewards);
+
+        let ferdie_balance_after = client
+            .free_balance(ferdie_address)
+            .await
+            .expect("getting Ferdie balance failed");
+        assert_eq!(ferdie_balance_before + 1, ferdie_balance_after);
+
+        let dev_contract_balance = client
+            .free_balance(dapps_staking_developer_contract.account_id)
+            .await
+            .expect("getting dev contract balance failed");
+        assert_eq!(1000000090, dev_contract_balance);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_remainder_to_first_winner<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // the remainder is given to the first-ranked winner
+        let set_remainder_destination = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_remainder_destination(RemainderDestination::FirstWinner);
+        client
+            .call(&ink_e2e::alice(), &set_remainder_destination)
+            .submit()
+            .await
+            .expect("set remainder destination failed");
+
+        // the first-ranked winner has no ratio
+        let set_ratio_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_ratio_distribution(vec![0, 50], 100);
+        client
+            .call(&ink_e2e::alice(), &set_ratio_distribution)
+            .submit()
+            .await
+            .expect("set ratio distribution failed");
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        let preview_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .preview_distribution(100, vec![charlie_address, dave_address]);
+        let distribution = client
+            .call(&ink_e2e::alice(), &preview_distribution)
+            .dry_run()
+            .await
+            .expect("fail to preview the distribution")
+            .return_value()
+            .expect("preview distribution failed");
+        assert_eq!((vec![(charlie_address, 50), (dave_address, 50)], 50), distribution);
+
+        // every ratio is zero: all the rewards go to the first-ranked winner
+        let set_ratio_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_ratio_distribution(vec![0], 100);
+        client
+            .call(&ink_e2e::alice(), &set_ratio_distribution)
+            .submit()
+            .await
+            .expect("set ratio distribution failed");
+
+        // the rewards wait for the end of the dispute window
+        let set_dispute_window = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_dispute_window(100);
+        client
+            .call(&ink_e2e::alice(), &set_dispute_window)
+            .submit()
+            .await
+            .expect("set dispute window failed");
+
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 13,
+                skip_reason: None,
+                rewards: 100,
+                winners: vec![dave_address],
+                winners_proof: None,
+            },
+        ).await;
+
+        // the remainder and its destination are kept with the pending raffle
+        let get_pending_raffle = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pending_raffle(13);
+        let pending_raffle = client
+            .call(&ink_e2e::alice(), &get_pending_raffle)
+            .dry_run()
+            .await
+            .expect("fail to get the pending raffle")
+            .return_value()
+            .expect("pending raffle not found");
+        assert_eq!(vec![(dave_address, 100)], pending_raffle.winners_rewards);
+        assert_eq!(100, pending_raffle.remainder);
+        assert_eq!(Some(RemainderDestination::FirstWinner), pending_raffle.remainder_destination);
+        assert_eq!(0, pending_raffle.protocol_fee);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_cooldown<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(200)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // the two last winners are excluded
+        let set_nb_winners_filtered = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_nb_winners_filtered(2);
+
+        client
+            .call(&ink_e2e::alice(), &set_nb_winners_filtered)
+            .submit()
+            .await
+            .expect("set nb winners filtered failed");
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        // dave wins the era 13
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 13,
+                skip_reason: None,
+                rewards: 100,
+                winners: [dave_address].to_vec(),
+                winners_proof: None,
+            },
+        ).await;
+
+        let get_last_winners = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_last_winners();
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert_eq!(vec![dave_address], last_winners);
+
+        // switch to a cooldown of 2 eras, dave is still excluded
+        let set_cooldown_eras = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_cooldown_eras(2);
+
+        let result = client.call(&ink_e2e::bob(), &set_cooldown_eras).submit().await;
+        assert!(result.is_err(), "Only the raffle manager can set the cooldown");
+
+        client
+            .call(&ink_e2e::alice(), &set_cooldown_eras)
+            .submit()
+            .await
+            .expect("set cooldown eras failed");
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert_eq!(vec![dave_address], last_winners);
+
+        let is_in_cooldown = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .is_in_cooldown(dave_address, 15);
+
+        let in_cooldown = client
+            .call(&ink_e2e::charlie(), &is_in_cooldown)
+            .dry_run()
+            .await
+            .expect("fail to check the cooldown")
+            .return_value();
+
+        assert!(in_cooldown);
+
+        let is_in_cooldown = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .is_in_cooldown(dave_address, 16);
+
+        let in_cooldown = client
+            .call(&ink_e2e::charlie(), &is_in_cooldown)
+            .dry_run()
+            .await
+            .expect("fail to check the cooldown")
+            .return_value();
+
+        assert!(!in_cooldown);
+
+        // charlie wins the era 14
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 14,
+                skip_reason: None,
+                rewards: 100,
+                winners: [charlie_address].to_vec(),
+                winners_proof: None,
+            },
+        ).await;
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert_eq!(vec![dave_address, charlie_address], last_winners);
+
+        // the era 15 is skipped, dave can participate again in the era 16
+        bob_sends_response(
+            &mut client,
+            &raffle_consumer_contract,
+            RaffleResponseMessage {
+                era: 15,
+                skip_reason: Some(SkipReason::NoParticipant),
+                rewards: 0,
+                winners: [].to_vec(),
+                winners_proof: None,
+            },
+        ).await;
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert_eq!(vec![charlie_address], last_winners);
+
+        // the excluded addresses are published in the kv store for the worker
+        let get_value = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_value(ink::selector_id!("LAST_WINNER").encode());
+
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the last winners in the kv store")
+            .return_value();
+
+        assert_eq!(Some(vec![charlie_address].encode()), value);
+
+        // charlie is no longer excluded when the next era is moved after his cooldown
+        let set_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_next_era(17);
+
+        client
+            .call(&ink_e2e::alice(), &set_next_era)
+            .submit()
+            .await
+            .expect("set next era failed");
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert!(last_winners.is_empty());
+
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the last winners in the kv store")
+            .return_value();
+
+        assert_eq!(Some(Vec::<AccountId>::new().encode()), value);
+
+        // switch back to the last winners, the two last winners are excluded again
+        let set_cooldown_eras = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_cooldown_eras(0);
+
+        client
+            .call(&ink_e2e::alice(), &set_cooldown_eras)
+            .submit()
+            .await
+            .expect("set cooldown eras failed");
+
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+
+        assert_eq!(vec![dave_address, charlie_address], last_winners);
+
+        let value = client
+            .call(&ink_e2e::charlie(), &get_value)
+            .dry_run()
+            .await
+            .expect("fail to get the last winners in the kv store")
+            .return_value();
+
+        assert_eq!(Some(vec![dave_address, charlie_address].encode()), value);
+
+        // no account is in cooldown anymore
+        let is_in_cooldown = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .is_in_cooldown(charlie_address, 16);
+
+        let in_cooldown = client
+            .call(&ink_e2e::charlie(), &is_in_cooldown)
+            .dry_run()
+            .await
+            .expect("fail to check the cooldown")
+            .return_value();
+
+        assert!(!in_cooldown);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_batched_raffles<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+
+        let response = |era: u32, rewards: u128, winners: Vec<AccountId>| {
+            RaffleResponseMessage {
+                era,
+                skip_reason: winners.is_empty().then_some(SkipReason::NoParticipant),
+                rewards,
+                winners,
+                winners_proof: None,
+            }
+        };
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(100)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // the eras must be contiguous
+        let batch = vec![response(13, 0, vec![]), response(15, 200, vec![charlie_address])];
+        let actions = vec![HandleActionInput::Reply(encode_response(batch))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "Eras must be contiguous");
+
+        // data is received for three eras
+        let batch = vec![
+            response(13, 0, vec![]),
+            response(14, 100, vec![dave_address]),
+            response(15, 200, vec![charlie_address]),
+        ];
+        let actions = vec![HandleActionInput::Reply(encode_response(batch))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+
+        let result = client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+        // events : MessageProcessedTo, RaffleSkipped, WinnerDrawn and RaffleDone by era
+        assert!(result.contains_event("Contracts", "ContractEmitted"));
+
+        let get_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_next_era();
+
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+
+        assert_eq!(16, next_era);
+
+        // the rewards of both raffles are transferred at once
+        let dev_contract_balance = client
+            .free_balance(dapps_staking_developer_contract.account_id)
+            .await
+            .expect("getting dev contract balance failed");
+
+        assert_eq!(1000000070, dev_contract_balance);
+
+        let reward_manager_contract_balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+
+        assert_eq!(1000000030, reward_manager_contract_balance);
+
+        // but each reward is credited to its own era
+        let get_pending_rewards_by_era = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_by_era(dave_address);
+
+        let pending_rewards_by_era = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value()
+            .expect("pending rewards by era failed");
+
+        assert_eq!(vec![(14, 10)], pending_rewards_by_era);
+
+        let get_pending_rewards_by_era = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .get_pending_rewards_by_era(charlie_address);
+
+        let pending_rewards_by_era = client
+            .call(&ink_e2e::alice(), &get_pending_rewards_by_era)
+            .dry_run()
+            .await
+            .expect("fail to get pending rewards by era")
+            .return_value()
+            .expect("pending rewards by era failed");
+
+        assert_eq!(vec![(15, 20)], pending_rewards_by_era);
+
+        Ok(())
+    }
+
+
+    #[ink_e2e::test]
+    async fn test_raffle_response_v2<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // data is received with the audit data
+        let audit = RaffleAudit {
+            nb_participants: 0,
+            total_tickets: 0,
+            snapshot_hash: ink::primitives::Hash::from([1u8; 32]),
+            randomness: ink::primitives::Hash::from([2u8; 32]),
+        };
+        let response = RaffleResponse::V2 {
+            pool_id: None,
+            responses: vec![RaffleResponseMessageV2 {
+                response: RaffleResponseMessage {
+                    era: 13,
+                    skip_reason: Some(SkipReason::NoParticipant),
+                    rewards: 0,
+                    winners: [].to_vec(),
+                    winners_proof: None,
+                },
+                audit: Some(audit.clone()),
+                next_random_commitment: None,
+                next_snapshot_commitment: None,
+            }],
+        };
+
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Unbound(response).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // check the audit data is saved
+        let get_raffle_audit = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_raffle_audit(13);
+
+        let raffle_audit = client
+            .call(&ink_e2e::charlie(), &get_raffle_audit)
+            .dry_run()
+            .await
+            .expect("fail to get raffle audit")
+            .return_value();
+
+        assert_eq!(Some(audit), raffle_audit);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_domain_separator<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // alice sets the chain id (no delay in the timelock)
+        let chain_id = ink::primitives::Hash::from([7u8; 32]);
+        let schedule_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(Operation::Contract(RaffleOperation::SetChainId(chain_id)));
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+
+        let execute_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .execute_operation(Operation::Contract(RaffleOperation::SetChainId(chain_id)));
+        client
+            .call(&ink_e2e::alice(), &execute_operation)
+            .submit()
+            .await
+            .expect("execute operation failed");
+
+        let response = || RaffleResponse::V2 {
+            pool_id: None,
+            responses: vec![RaffleResponseMessageV2 {
+                response: RaffleResponseMessage {
+                    era: 13,
+                    skip_reason: Some(SkipReason::VotingSubPeriod),
+                    rewards: 0,
+                    winners: [].to_vec(),
+                    winners_proof: None,
+                },
+                audit: None,
+                next_random_commitment: None,
+                next_snapshot_commitment: None,
+            }],
+        };
+
+        // the message without envelope sent by the first version of the worker is rejected
+        let legacy_response = RaffleResponseMessageV1 {
+            era: 13,
+            skipped: true,
+            rewards: 0,
+            winners: [].to_vec(),
+        };
+        let actions = vec![HandleActionInput::Reply(legacy_response.encode())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The migration window is over");
+
+        // the response without domain is rejected
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Unbound(response()).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The domain is missing");
+
+        // the response produced for another contract is rejected
+        let bound_response = BoundRaffleResponse {
+            domain: DomainSeparator {
+                chain_id,
+                contract: reward_manager_contract.account_id,
+            },
+            response: response(),
+        };
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Bound(bound_response).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The domain is invalid");
+
+        // the response produced for this deployment is accepted
+        let bound_response = BoundRaffleResponse {
+            domain: DomainSeparator {
+                chain_id,
+                contract: raffle_consumer_contract.account_id,
+            },
+            response: response(),
+        };
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Bound(bound_response).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_pools<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // the developer contract of another dApp
+        let pool_dev_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+
+        let grant_whitelisted_role = pool_dev_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .grant_role(WHITELISTED_ADDRESS, raffle_consumer_contract.account_id);
+        client
+            .call(&ink_e2e::alice(), &grant_whitelisted_role)
+            .submit()
+            .await
+            .expect("grant whitelisted role failed");
+
+        let fund_pool_dev_contract = pool_dev_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+        client
+            .call(&ink_e2e::alice(), &fund_pool_dev_contract)
+            .value(100)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let response = || RaffleResponse::V2 {
+            pool_id: Some(1),
+            responses: vec![RaffleResponseMessageV2 {
+                response: RaffleResponseMessage {
+                    era: 20,
+                    skip_reason: None,
+                    rewards: 100,
+                    winners: vec![charlie_address],
+                    winners_proof: None,
+                },
+                audit: None,
+                next_random_commitment: None,
+                next_snapshot_commitment: None,
+            }],
+        };
+
+        // the pool is not registered yet
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Unbound(response()).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The pool is unknown");
+
+        let set_pool_ratio_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_ratio_distribution(1, vec![50], 100);
+        let result = client.call(&ink_e2e::alice(), &set_pool_ratio_distribution).submit().await;
+        assert!(result.is_err(), "The pool is unknown");
+
+        // alice registers the pool (no delay in the timelock)
+        let operation = Operation::Contract(RaffleOperation::SetPoolDappsStakingDeveloperAddress(
+            1,
+            pool_dev_contract.account_id,
+        ));
+        let schedule_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(operation.clone());
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+
+        let execute_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .execute_operation(operation);
+        client
+            .call(&ink_e2e::alice(), &execute_operation)
+            .submit()
+            .await
+            .expect("execute operation failed");
+
+        // the pool has its own ratio distribution and era cursor
+        let set_pool_ratio_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_ratio_distribution(1, vec![50], 100);
+        client
+            .call(&ink_e2e::alice(), &set_pool_ratio_distribution)
+            .submit()
+            .await
+            .expect("set pool ratio distribution failed");
+
+        let set_pool_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_next_era(1, 20);
+        client
+            .call(&ink_e2e::alice(), &set_pool_next_era)
+            .submit()
+            .await
+            .expect("set pool next era failed");
+
+        // the pool has its own winners filter and protocol fee
+        let set_pool_nb_winners_filtered = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_nb_winners_filtered(1, 1);
+        client
+            .call(&ink_e2e::alice(), &set_pool_nb_winners_filtered)
+            .submit()
+            .await
+            .expect("set pool nb winners filtered failed");
+
+        let set_pool_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_protocol_fee(1, 100);
+        client
+            .call(&ink_e2e::alice(), &set_pool_protocol_fee)
+            .submit()
+            .await
+            .expect("set pool protocol fee failed");
+
+        let get_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_protocol_fee();
+        let protocol_fee = client
+            .call(&ink_e2e::charlie(), &get_protocol_fee)
+            .dry_run()
+            .await
+            .expect("fail to get protocol fee")
+            .return_value();
+        assert_eq!(0, protocol_fee);
+
+        let set_pool_protocol_fee = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_protocol_fee(1, 0);
+        client
+            .call(&ink_e2e::alice(), &set_pool_protocol_fee)
+            .submit()
+            .await
+            .expect("set pool protocol fee failed");
+
+        // the pool has its own remainder destination, used in the preview of its distribution
+        let set_pool_remainder_destination = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_remainder_destination(1, RemainderDestination::FirstWinner);
+        client
+            .call(&ink_e2e::alice(), &set_pool_remainder_destination)
+            .submit()
+            .await
+            .expect("set pool remainder destination failed");
+
+        let get_remainder_destination = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_remainder_destination();
+        let remainder_destination = client
+            .call(&ink_e2e::charlie(), &get_remainder_destination)
+            .dry_run()
+            .await
+            .expect("fail to get remainder destination")
+            .return_value();
+        assert_eq!(RemainderDestination::DappsStakingDeveloper, remainder_destination);
+
+        let preview_pool_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .preview_pool_distribution(1, 100, vec![charlie_address]);
+        let preview = client
+            .call(&ink_e2e::charlie(), &preview_pool_distribution)
+            .dry_run()
+            .await
+            .expect("fail to preview pool distribution")
+            .return_value()
+            .expect("preview pool distribution failed");
+        assert_eq!((vec![(charlie_address, 100)], 0), preview);
+
+        let preview_distribution = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .preview_distribution(100, vec![charlie_address]);
+        let preview = client
+            .call(&ink_e2e::charlie(), &preview_distribution)
+            .dry_run()
+            .await
+            .expect("fail to preview distribution")
+            .return_value()
+            .expect("preview distribution failed");
+        assert_eq!((vec![(charlie_address, 10)], 90), preview);
+
+        let set_pool_remainder_destination = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_remainder_destination(1, RemainderDestination::DappsStakingDeveloper);
+        client
+            .call(&ink_e2e::alice(), &set_pool_remainder_destination)
+            .submit()
+            .await
+            .expect("set pool remainder destination failed");
+
+        // data is received for the pool
+        let actions = vec![HandleActionInput::Reply(ResponseEnvelope::Unbound(response()).encode_action())];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // only the era cursor of the pool is updated
+        let get_pool_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pool_next_era(1);
+        let pool_next_era = client
+            .call(&ink_e2e::charlie(), &get_pool_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get pool next era")
+            .return_value()
+            .expect("pool next era failed");
+        assert_eq!(21, pool_next_era);
+
+        let get_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_next_era();
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+        assert_eq!(13, next_era);
+
+        let get_pool_raffle_result = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pool_raffle_result(1, 20);
+        let pool_raffle_result = client
+            .call(&ink_e2e::charlie(), &get_pool_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get pool raffle result")
+            .return_value();
+        assert_eq!(Some(vec![(charlie_address, 50)]), pool_raffle_result.map(|r| r.winners));
+
+        // the winner is excluded from the next raffle of the pool only
+        let get_pool_last_winners = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pool_last_winners(1);
+        let pool_last_winners = client
+            .call(&ink_e2e::charlie(), &get_pool_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get pool last winners")
+            .return_value();
+        assert_eq!(vec![charlie_address], pool_last_winners);
+
+        let get_last_winners = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_last_winners();
+        let last_winners = client
+            .call(&ink_e2e::charlie(), &get_last_winners)
+            .dry_run()
+            .await
+            .expect("fail to get last winners")
+            .return_value();
+        assert!(last_winners.is_empty());
+
+        // the rewards are withdrawn from the developer contract of the pool
+        let pool_dev_contract_balance = client
+            .free_balance(pool_dev_contract.account_id)
+            .await
+            .expect("getting pool dev contract balance failed");
+        assert_eq!(1000000050, pool_dev_contract_balance);
+
+        let reward_manager_contract_balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+        assert_eq!(1000000050, reward_manager_contract_balance);
+
+        // the default pool cannot be removed
+        let operation = Operation::Contract(RaffleOperation::RemovePool(0));
+        let schedule_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(operation.clone());
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+        let execute_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .execute_operation(operation);
+        let result = client.call(&ink_e2e::alice(), &execute_operation).submit().await;
+        assert!(result.is_err(), "The default pool cannot be removed");
+
+        // alice removes the pool
+        alice_executes_operation(
+            &mut client,
+            &raffle_consumer_contract,
+            Operation::Contract(RaffleOperation::RemovePool(1)),
+        ).await;
+
+        let get_pools = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pools();
+        let pools = client
+            .call(&ink_e2e::charlie(), &get_pools)
+            .dry_run()
+            .await
+            .expect("fail to get pools")
+            .return_value();
+        assert!(pools.is_empty());
+
+        let set_pool_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_pool_next_era(1, 21);
+        let result = client.call(&ink_e2e::alice(), &set_pool_next_era).submit().await;
+        assert!(result.is_err(), "The pool has been removed");
+
+        let finalize_pool_raffle = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .finalize_pool_raffle(1, 21);
+        let result = client.call(&ink_e2e::alice(), &finalize_pool_raffle).dry_run().await;
+        assert_eq!(
+            Err(RaffleError::UnknownPool),
+            result.expect("fail to finalize pool raffle").return_value(),
+        );
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_pause<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // only the guardian can pause the contract
+        let pause = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .pause();
+        let result = client.call(&ink_e2e::bob(), &pause).submit().await;
+        assert!(result.is_err(), "Only the guardian can pause the contract");
+
+        // alice is the guardian since the instantiation
+        client
+            .call(&ink_e2e::alice(), &pause)
+            .submit()
+            .await
+            .expect("pause failed");
+
+        // the responses are rejected while the contract is paused
+        let response = RaffleResponseMessage {
+            era: 13,
+            skip_reason: Some(SkipReason::VotingSubPeriod),
+            rewards: 0,
+            winners: [].to_vec(),
+            winners_proof: None,
+        };
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The contract is paused");
+
+        // only the admin can unpause the contract
+        let unpause = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .unpause();
+        let result = client.call(&ink_e2e::bob(), &unpause).submit().await;
+        assert!(result.is_err(), "Only the admin can unpause the contract");
+
+        client
+            .call(&ink_e2e::alice(), &unpause)
+            .submit()
+            .await
+            .expect("unpause failed");
+
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_timelock<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        let ferdie_address = ink::primitives::AccountId::from(ink_e2e::ferdie().public_key().0);
+
+        // the delay cannot be zero
+        let schedule_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(Operation::SetMinDelay(0));
+        let result = client.call(&ink_e2e::alice(), &schedule_operation).submit().await;
+        assert!(result.is_err(), "The delay must be at least one block");
+
+        // only the admin can schedule an operation
+        let operation = Operation::Contract(RaffleOperation::SetRewardManagerAddress(ferdie_address));
+        let schedule_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .schedule_operation(operation.clone());
+        let result = client.call(&ink_e2e::bob(), &schedule_operation).submit().await;
+        assert!(result.is_err(), "Only the admin can schedule an operation");
+
+        // the operation cannot be executed once cancelled
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+        let cancel_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .cancel_operation(operation.clone());
+        client
+            .call(&ink_e2e::alice(), &cancel_operation)
+            .submit()
+            .await
+            .expect("cancel operation failed");
+        let execute_operation = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .execute_operation(operation.clone());
+        let result = client.call(&ink_e2e::alice(), &execute_operation).submit().await;
+        assert!(result.is_err(), "The operation is cancelled");
+
+        // the deprecated message schedules the operation, the next call executes it
+        let set_reward_manager_address = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_reward_manager_address(ferdie_address);
+        client
+            .call(&ink_e2e::alice(), &set_reward_manager_address)
+            .submit()
+            .await
+            .expect("set reward manager address failed");
+
+        let get_reward_manager_address = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_reward_manager_address();
+        let reward_manager_address = client
+            .call(&ink_e2e::alice(), &get_reward_manager_address)
+            .dry_run()
+            .await
+            .expect("fail to get reward manager address")
+            .return_value();
+        assert_eq!(Some(reward_manager_contract.account_id), reward_manager_address);
+
+        client
+            .call(&ink_e2e::alice(), &set_reward_manager_address)
+            .submit()
+            .await
+            .expect("set reward manager address failed");
+
+        let reward_manager_address = client
+            .call(&ink_e2e::alice(), &get_reward_manager_address)
+            .dry_run()
+            .await
+            .expect("fail to get reward manager address")
+            .return_value();
+        assert_eq!(Some(ferdie_address), reward_manager_address);
+
+        // the operation cannot be executed before the end of the delay
+        alice_executes_operation(&mut client, &raffle_consumer_contract, Operation::SetMinDelay(100)).await;
+
+        let get_min_delay = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_min_delay();
+        let min_delay = client
+            .call(&ink_e2e::alice(), &get_min_delay)
+            .dry_run()
+            .await
+            .expect("fail to get min delay")
+            .return_value();
+        assert_eq!(100, min_delay);
+
+        client
+            .call(&ink_e2e::alice(), &schedule_operation)
+            .submit()
+            .await
+            .expect("schedule operation failed");
+        let result = client.call(&ink_e2e::alice(), &execute_operation).submit().await;
+        assert!(result.is_err(), "The delay is not over");
+
+        // the withdrawal from the reward manager is also scheduled first
+        let reward_manager_balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+
+        let withdraw = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .withdraw(100);
+        client
+            .call(&ink_e2e::alice(), &withdraw)
+            .submit()
+            .await
+            .expect("withdraw failed");
+
+        let balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+        assert_eq!(reward_manager_balance, balance);
+
+        client
+            .call(&ink_e2e::alice(), &withdraw)
+            .submit()
+            .await
+            .expect("withdraw failed");
+
+        let balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+        assert_eq!(reward_manager_balance - 100, balance);
+
+        // a whitelisted address withdraws directly from the surplus
+        let grant_whitelisted_role = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .grant_role(WHITELISTED_ADDRESS, ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0));
+        client
+            .call(&ink_e2e::alice(), &grant_whitelisted_role)
+            .submit()
+            .await
+            .expect("grant whitelisted address failed");
+
+        let withdraw = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .withdraw(50);
+        client
+            .call(&ink_e2e::charlie(), &withdraw)
+            .submit()
+            .await
+            .expect("withdraw by whitelisted address failed");
+
+        let balance = client
+            .free_balance(reward_manager_contract.account_id)
+            .await
+            .expect("getting reward manager contract balance failed");
+        assert_eq!(reward_manager_balance - 150, balance);
+
+        // but not above the surplus
+        let withdraw = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .withdraw(balance + 1);
+        let result = client.call(&ink_e2e::charlie(), &withdraw).submit().await;
+        assert!(result.is_err(), "Only the surplus can be withdrawn");
+
+        // and the other accounts cannot withdraw
+        let withdraw = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .withdraw(50);
+        let result = client.call(&ink_e2e::dave(), &withdraw).submit().await;
+        assert!(result.is_err(), "Dave is neither admin nor whitelisted");
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_attestor_expiry<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        let bob_address = ink::primitives::AccountId::from(ink_e2e::bob().public_key().0);
+        let quote_hash = ink::primitives::Hash::from([1u8; 32]);
+        let measurement = ink::primitives::Hash::from([2u8; 32]);
+
+        // bob is registered as attestor with an attestation already expired
+        let register_attestor = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .register_attestor_with_evidence(bob_address, quote_hash, measurement, 0);
+        client
+            .call(&ink_e2e::alice(), &register_attestor)
+            .submit()
+            .await
+            .expect("register attestor failed");
+
+        let get_active_attestors = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_active_attestors();
+        let active_attestors = client
+            .call(&ink_e2e::charlie(), &get_active_attestors)
+            .dry_run()
+            .await
+            .expect("fail to get active attestors")
+            .return_value();
+        assert!(active_attestors.is_empty());
+
+        let response = RaffleResponseMessage {
+            era: 13,
+            skip_reason: Some(SkipReason::VotingSubPeriod),
+            rewards: 0,
+            winners: [].to_vec(),
+            winners_proof: None,
+        };
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The attestation is expired");
+
+        // bob is registered again with a new attestation
+        let register_attestor = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .register_attestor_with_evidence(bob_address, quote_hash, measurement, 1000);
+        client
+            .call(&ink_e2e::alice(), &register_attestor)
+            .submit()
+            .await
+            .expect("register attestor failed");
+
+        let active_attestors = client
+            .call(&ink_e2e::charlie(), &get_active_attestors)
+            .dry_run()
+            .await
+            .expect("fail to get active attestors")
+            .return_value();
+        assert_eq!(1, active_attestors.len());
+        assert_eq!(bob_address, active_attestors[0].0);
+
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // the rotation to the same key renews the attestation
+        let rotate_attestor = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rotate_attestor(bob_address, bob_address, quote_hash, measurement, 2000);
+        client
+            .call(&ink_e2e::alice(), &rotate_attestor)
+            .submit()
+            .await
+            .expect("rotate attestor failed");
+
+        let active_attestors = client
+            .call(&ink_e2e::charlie(), &get_active_attestors)
+            .dry_run()
+            .await
+            .expect("fail to get active attestors")
+            .return_value();
+        assert_eq!(1, active_attestors.len());
+        assert_eq!(bob_address, active_attestors[0].0);
+        assert_eq!(2000, active_attestors[0].1.expires_at - active_attestors[0].1.registered_at);
+
+        // the attestor granted without evidence expires after the default validity
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let grant_role = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .grant_role(ATTESTOR_ROLE, charlie_address);
+        client
+            .call(&ink_e2e::alice(), &grant_role)
+            .submit()
+            .await
+            .expect("grant charlie as attestor failed");
+
+        let get_attestor_info = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_attestor_info(charlie_address);
+        let attestor_info = client
+            .call(&ink_e2e::charlie(), &get_attestor_info)
+            .dry_run()
+            .await
+            .expect("fail to get attestor info")
+            .return_value()
+            .expect("charlie should be registered");
+        assert_eq!(50_400, attestor_info.expires_at - attestor_info.registered_at);
+
+        // the attestor revoked with the role is removed from the registry
+        let revoke_role = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .revoke_role(ATTESTOR_ROLE, charlie_address);
+        client
+            .call(&ink_e2e::alice(), &revoke_role)
+            .submit()
+            .await
+            .expect("revoke charlie as attestor failed");
+
+        let attestor_info = client
+            .call(&ink_e2e::charlie(), &get_attestor_info)
+            .dry_run()
+            .await
+            .expect("fail to get attestor info")
+            .return_value();
+        assert_eq!(None, attestor_info);
+
+        let active_attestors = client
+            .call(&ink_e2e::charlie(), &get_active_attestors)
+            .dry_run()
+            .await
+            .expect("fail to get active attestors")
+            .return_value();
+        assert_eq!(1, active_attestors.len());
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_quorum<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        let bob_address = ink::primitives::AccountId::from(ink_e2e::bob().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let quote_hash = ink::primitives::Hash::from([1u8; 32]);
+        let measurement = ink::primitives::Hash::from([2u8; 32]);
+
+        // the threshold cannot be above the number of active attestors
+        let set_quorum_threshold = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_quorum_threshold(2);
+        let result = client.call(&ink_e2e::alice(), &set_quorum_threshold).submit().await;
+        assert!(result.is_err(), "The quorum cannot be reached");
+
+        // bob and dave are registered as attestors
+        for attestor in [bob_address, dave_address] {
+            let register_attestor = raffle_consumer_contract
+                .call_builder::<raffle_consumer::Contract>()
+                .register_attestor_with_evidence(attestor, quote_hash, measurement, 1000);
+            client
+                .call(&ink_e2e::alice(), &register_attestor)
+                .submit()
+                .await
+                .expect("register attestor failed");
+        }
+
+        client
+            .call(&ink_e2e::alice(), &set_quorum_threshold)
+            .submit()
+            .await
+            .expect("set quorum threshold failed");
+
+        let response = |skip_reason| RaffleResponseMessage {
+            era: 13,
+            skip_reason: Some(skip_reason),
+            rewards: 0,
+            winners: [].to_vec(),
+            winners_proof: None,
+        };
+
+        // bob submits a wrong response
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response(SkipReason::NoReward)]))];
+        let wrong_rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &wrong_rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // bob corrects his submission
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response(SkipReason::VotingSubPeriod)]))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let get_quorum_submissions = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_quorum_submissions(13);
+        let submissions = client
+            .call(&ink_e2e::charlie(), &get_quorum_submissions)
+            .dry_run()
+            .await
+            .expect("fail to get quorum submissions")
+            .return_value();
+        assert_eq!(1, submissions.len());
+        assert_eq!(bob_address, submissions[0].0);
+
+        // the quorum is not reached => the era is not done
+        let get_next_era = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_next_era();
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+        assert_eq!(13, next_era);
+
+        // bob cannot be revoked while the quorum needs him
+        let revoke_attestor = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .revoke_attestor(bob_address);
+        let result = client.call(&ink_e2e::alice(), &revoke_attestor).submit().await;
+        assert!(result.is_err(), "The quorum cannot be reached without bob");
+
+        // dave submits the same response => the quorum is reached
+        client
+            .call(&ink_e2e::dave(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let next_era = client
+            .call(&ink_e2e::charlie(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+        assert_eq!(14, next_era);
+
+        // the submissions are cleared
+        let submissions = client
+            .call(&ink_e2e::charlie(), &get_quorum_submissions)
+            .dry_run()
+            .await
+            .expect("fail to get quorum submissions")
+            .return_value();
+        assert!(submissions.is_empty());
+
+        // a late submission is rejected
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The era is already done");
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_bad_attestor<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is not granted as attestor => it should not be able to send a message
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], vec![]);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(
+            result.is_err(),
+            "only attestor should be able to send messages"
+        );
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // then bob is able to send a message
+        let result = client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq failed");
+        // no event
+        assert!(!result.contains_event("Contracts", "ContractEmitted"));
+
+        Ok(())
+    }
+
+
+    #[ink_e2e::test]
+    async fn test_winners_proof<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // fund the developer contract
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(100)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        // the winners must come with the proof
+        let set_winners_proof_required = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .set_winners_proof_required(true);
+        client
+            .call(&ink_e2e::alice(), &set_winners_proof_required)
+            .submit()
+            .await
+            .expect("set winners proof required failed");
+
+        // snapshot of the participants: charlie and dave have 50 tickets each
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let dave_address = ink::primitives::AccountId::from(ink_e2e::dave().public_key().0);
+        let ranges = [
+            TicketRange { account: charlie_address, start: 0, end: 50 },
+            TicketRange { account: dave_address, start: 50, end: 100 },
+        ];
+        let leaves = [leaf_hash(&ranges[0]), leaf_hash(&ranges[1])];
+        let random = [7u8; 32];
+
+        let winners_proof = |random: HashValue| {
+            let ticket = draw_ticket(&random, 15, 0, 100).unwrap();
+            let index = if ticket < 50 { 0 } else { 1 };
+            WinnersProof {
+                snapshot_root: node_hash(&leaves[0], &leaves[1]),
+                total_tickets: 100,
+                random,
+                draws: vec![TicketProof {
+                    range: ranges[index].clone(),
+                    proof: vec![leaves[1 - index]],
+                }],
+            }
+        };
+        let response = |era: u32,
+                        winners_proof: Option<WinnersProof>,
+                        commitment: Option<HashValue>,
+                        snapshot_commitment: Option<HashValue>| {
+            let winners = winners_proof
+                .as_ref()
+                .map(|proof| vec![proof.draws[0].range.account])
+                .unwrap_or_default();
+            let skip_reason = winners_proof.is_none().then_some(SkipReason::VotingSubPeriod);
+            let rewards = if winners_proof.is_some() { 100 } else { 0 };
+            let responses = vec![RaffleResponseMessageV2 {
+                response: RaffleResponseMessage {
+                    era,
+                    skip_reason,
+                    rewards,
+                    winners,
+                    winners_proof,
+                },
+                audit: None,
+                next_random_commitment: commitment,
+                next_snapshot_commitment: snapshot_commitment,
+            }];
+            ResponseEnvelope::Unbound(RaffleResponse::V2 { pool_id: None, responses }).encode_action()
+        };
+
+        // the era 13 is skipped and the random value of the era 15 is committed
+        let actions = vec![HandleActionInput::Reply(response(13, None, Some(random_commitment(&random)), None))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let get_random_commitment = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pool_random_commitment(0, 15);
+        let commitment = client
+            .call(&ink_e2e::charlie(), &get_random_commitment)
+            .dry_run()
+            .await
+            .expect("fail to get random commitment")
+            .return_value();
+        assert_eq!(Some(random_commitment(&random)), commitment);
+
+        // no random value and no snapshot have been committed for the era 14
+        let actions = vec![HandleActionInput::Reply(response(14, Some(winners_proof(random)), None, None))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The random value is not committed");
+
+        // the era 14 is skipped and the snapshot of the era 15 is committed
+        let snapshot_root = node_hash(&leaves[0], &leaves[1]);
+        let actions = vec![HandleActionInput::Reply(response(14, None, None, Some(snapshot_root)))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let get_snapshot_commitment = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_pool_snapshot_commitment(0, 15);
+        let snapshot_commitment = client
+            .call(&ink_e2e::charlie(), &get_snapshot_commitment)
+            .dry_run()
+            .await
+            .expect("fail to get snapshot commitment")
+            .return_value();
+        assert_eq!(Some(snapshot_root), snapshot_commitment);
+
+        // the random value must match the commitment
+        let actions = vec![HandleActionInput::Reply(response(15, Some(winners_proof([8u8; 32])), None, None))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The random value doesn't match the commitment");
+
+        // the snapshot must match the commitment: a valid proof built on another snapshot is rejected
+        let whale_range = TicketRange { account: charlie_address, start: 0, end: 100 };
+        let whale_proof = WinnersProof {
+            snapshot_root: leaf_hash(&whale_range),
+            total_tickets: 100,
+            random,
+            draws: vec![TicketProof { range: whale_range, proof: vec![] }],
+        };
+        let actions = vec![HandleActionInput::Reply(response(15, Some(whale_proof), None, None))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        let result = client.call(&ink_e2e::bob(), &rollup_cond_eq).submit().await;
+        assert!(result.is_err(), "The snapshot doesn't match the commitment");
+
+        // the winner drawn with the committed random value is accepted
+        let winner = winners_proof(random).draws[0].range.account;
+        let actions = vec![HandleActionInput::Reply(response(15, Some(winners_proof(random)), None, None))];
+        let rollup_cond_eq = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let get_raffle_result = raffle_consumer_contract
+            .call_builder::<raffle_consumer::Contract>()
+            .get_raffle_result(15);
+        let raffle_result = client
+            .call(&ink_e2e::charlie(), &get_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get raffle result")
+            .return_value()
+            .expect("raffle result not found");
+        assert_eq!(vec![(winner, 10)], raffle_result.winners);
+
+        Ok(())
+    }
+
+    #[ink_e2e::test]
+    async fn test_bad_messages(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+        let raffle_consumer_contract = alice_instantiates_raffle_consumer_contract(
+            &mut client,
+            dapps_staking_developer_contract.account_id,
+            reward_manager_contract.account_id,
+        ).await;
+
+        // configure the contracts
+        alice_configure_contracts(
+            &mut client,
+            &reward_manager_contract,
+            &dapps_staking_developer_contract,
+            &raffle_consumer_contract
+        ).await;
+
+        // bob is granted as attestor
+        alice_grants_bob_as_attestor(&mut client, &raffle_consumer_contract).await;
+
+        let actions = vec![HandleActionInput::Reply(58u128.encode())];
         let rollup_cond_eq = raffle_consumer_contract
             .call_builder::<raffle_consumer::Contract>()
             .rollup_cond_eq(vec![], vec![], actions.clone());
@@ -569,5 +3686,241 @@ mod e2e_tests {
         Ok(())
     }
 
+    ///
+    /// Test the upgrade of the contract deployed with the version 3.0.0
+    /// Alice is the owner and the sudo account
+    /// Bob is the attestor
+    ///
+    #[ink_e2e::test]
+    async fn test_upgrade_from_v3(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+        use ink_e2e::subxt::dynamic::Value;
+
+        // given
+        let reward_manager_contract = alice_instantiates_reward_manager_contract(&mut client).await;
+        let dapps_staking_developer_contract = alice_instantiates_dapps_staking_developer_contract(&mut client).await;
+
+        // alice instantiates the version 3.0.0 of the contract
+        let code = std::fs::read("../artifacts/raffle_consumer/v3.0.0/raffle_consumer.wasm")
+            .expect("fail to read the code of the version 3.0.0");
+        // selector of the constructor `new` in the version 3.0.0
+        let mut data = vec![0x9b, 0xae, 0x9d, 0x5e];
+        data.extend((dapps_staking_developer_contract.account_id, reward_manager_contract.account_id).encode());
+        let events = client
+            .runtime_call(
+                &ink_e2e::alice(),
+                "Contracts",
+                "instantiate_with_code",
+                vec![
+                    Value::u128(0),
+                    Value::named_composite(vec![
+                        ("ref_time", Value::u128(500_000_000_000)),
+                        ("proof_size", Value::u128(2_000_000)),
+                    ]),
+                    Value::unnamed_variant("None", vec![]),
+                    Value::from_bytes(code),
+                    Value::from_bytes(data),
+                    Value::from_bytes(Vec::<u8>::new()),
+                ],
+            )
+            .await
+            .expect("instantiate the version 3.0.0 failed");
+
+        // the event Instantiated contains the deployer and the address of the contract
+        let raffle_consumer_address = events
+            .iter()
+            .filter_map(|event| event.ok())
+            .find(|event| event.pallet_name() == "Contracts" && event.variant_name() == "Instantiated")
+            .map(|event| AccountId::try_from(&event.field_bytes()[32..64]).expect("invalid address"))
+            .expect("event Instantiated not found");
+        let raffle_consumer = || ink_e2e::create_call_builder::<raffle_consumer::Contract>(raffle_consumer_address);
+
+        // configure the contracts
+        let grant_whitelisted_role = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .grant_role(WHITELISTED_ADDRESS, raffle_consumer_address);
+        client
+            .call(&ink_e2e::alice(), &grant_whitelisted_role)
+            .submit()
+            .await
+            .expect("grant whitelisted role failed");
+
+        let grant_reward_manager_role = reward_manager_contract
+            .call_builder::<reward_manager::Contract>()
+            .grant_role(REWARD_MANAGER_ROLE, raffle_consumer_address);
+        client
+            .call(&ink_e2e::alice(), &grant_reward_manager_role)
+            .submit()
+            .await
+            .expect("grant reward manager role failed");
+
+        let fund_dev_contract = dapps_staking_developer_contract
+            .call_builder::<dapps_staking_developer::Contract>()
+            .fund();
+        client
+            .call(&ink_e2e::alice(), &fund_dev_contract)
+            .value(200)
+            .submit()
+            .await
+            .expect("fund dev contract failed");
+
+        // the messages below have the same selector and arguments in the version 3.0.0
+        let set_ratio_distribution = raffle_consumer().set_ratio_distribution(vec![10], 100);
+        client
+            .call(&ink_e2e::alice(), &set_ratio_distribution)
+            .submit()
+            .await
+            .expect("set ratio distribution failed");
+
+        let set_next_era = raffle_consumer().set_next_era(13);
+        client
+            .call(&ink_e2e::alice(), &set_next_era)
+            .submit()
+            .await
+            .expect("set next era failed");
+
+        let bob_address = ink::primitives::AccountId::from(ink_e2e::bob().public_key().0);
+        let grant_role = raffle_consumer().grant_role(ATTESTOR_ROLE, bob_address);
+        client
+            .call(&ink_e2e::alice(), &grant_role)
+            .submit()
+            .await
+            .expect("grant bob as attestor failed");
+
+        // the raffle is done with the version 3.0.0
+        let charlie_address = ink::primitives::AccountId::from(ink_e2e::charlie().public_key().0);
+        let response = RaffleResponseMessageV1 {
+            era: 13,
+            skipped: false,
+            rewards: 100,
+            winners: vec![charlie_address],
+        };
+        let actions = vec![HandleActionInput::Reply(response.encode())];
+        let rollup_cond_eq = raffle_consumer().rollup_cond_eq(vec![], vec![], actions);
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        // the version 3.0.0 cannot set the code hash, the code is replaced by sudo
+        let code_hash = client
+            .upload("raffle_consumer", &ink_e2e::alice())
+            .submit()
+            .await
+            .expect("upload failed")
+            .code_hash;
+        client
+            .runtime_call(
+                &ink_e2e::alice(),
+                "Sudo",
+                "sudo",
+                vec![Value::unnamed_variant(
+                    "Contracts",
+                    vec![Value::named_variant(
+                        "set_code",
+                        vec![
+                            ("dest", Value::unnamed_variant("Id", vec![Value::from_bytes(raffle_consumer_address)])),
+                            ("code_hash", Value::from_bytes(code_hash)),
+                        ],
+                    )],
+                )],
+            )
+            .await
+            .expect("set code failed");
+
+        // the storage has not been migrated yet
+        let get_storage_version = raffle_consumer().get_storage_version();
+        let storage_version = client
+            .call(&ink_e2e::alice(), &get_storage_version)
+            .dry_run()
+            .await
+            .expect("fail to get storage version")
+            .return_value();
+        assert_eq!(0, storage_version);
+
+        // only the admin can migrate the storage
+        let migrate = raffle_consumer().migrate();
+        let result = client.call(&ink_e2e::bob(), &migrate).submit().await;
+        assert!(result.is_err(), "Only the admin can migrate the storage");
+
+        client
+            .call(&ink_e2e::alice(), &migrate)
+            .submit()
+            .await
+            .expect("migrate failed");
+
+        let result = client.call(&ink_e2e::alice(), &migrate).submit().await;
+        assert!(result.is_err(), "The storage is already migrated");
+
+        // the raffle data and the roles written by the version 3.0.0 are kept
+        let get_ratio_distribution = raffle_consumer().get_ratio_distribution();
+        let ratio_distribution = client
+            .call(&ink_e2e::alice(), &get_ratio_distribution)
+            .dry_run()
+            .await
+            .expect("fail to get ratio distribution")
+            .return_value();
+        assert_eq!(vec![10], ratio_distribution);
+
+        let get_next_era = raffle_consumer().get_next_era();
+        let next_era = client
+            .call(&ink_e2e::alice(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+        assert_eq!(14, next_era);
+
+        let alice_address = ink::primitives::AccountId::from(ink_e2e::alice().public_key().0);
+        let has_role = raffle_consumer().has_role(lucky::traits::pausable::GUARDIAN_ROLE, alice_address);
+        let is_guardian = client
+            .call(&ink_e2e::alice(), &has_role)
+            .dry_run()
+            .await
+            .expect("fail to check the role")
+            .return_value();
+        assert!(is_guardian, "The admin calling migrate is the guardian");
+
+        // bob has been granted before the registry of attestors and still sends the responses
+        let response = RaffleResponseMessage {
+            era: 14,
+            skip_reason: None,
+            rewards: 100,
+            winners: vec![charlie_address],
+            winners_proof: None,
+        };
+        let actions = vec![HandleActionInput::Reply(encode_response(vec![response]))];
+        let rollup_cond_eq = raffle_consumer().rollup_cond_eq(vec![], vec![], actions);
+
+        // the raffle is done with the new version
+        client
+            .call(&ink_e2e::bob(), &rollup_cond_eq)
+            .submit()
+            .await
+            .expect("rollup cond eq should be ok");
+
+        let next_era = client
+            .call(&ink_e2e::alice(), &get_next_era)
+            .dry_run()
+            .await
+            .expect("fail to get next era")
+            .return_value()
+            .expect("next era failed");
+        assert_eq!(15, next_era);
+
+        let get_raffle_result = raffle_consumer().get_raffle_result(14);
+        let raffle_result = client
+            .call(&ink_e2e::alice(), &get_raffle_result)
+            .dry_run()
+            .await
+            .expect("fail to get raffle result")
+            .return_value()
+            .expect("raffle result not found");
+        assert_eq!(vec![(charlie_address, 10)], raffle_result.winners);
+
+        Ok(())
+    }
+
 
 }
@@ -0,0 +1,116 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+/// Minimal PSP22 token used to test the rewards paid in token
+#[ink::contract]
+pub mod psp22_mock {
+
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+    use lucky::traits::reward::PSP22Error;
+
+    #[ink::trait_definition]
+    pub trait PSP22 {
+        #[ink(message)]
+        fn total_supply(&self) -> Balance;
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance;
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance;
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error>;
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            data: Vec<u8>,
+        ) -> Result<(), PSP22Error>;
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error>;
+    }
+
+    /// Contract storage
+    #[derive(Default)]
+    #[ink(storage)]
+    pub struct Contract {
+        total_supply: Balance,
+        balances: Mapping<AccountId, Balance>,
+        allowances: Mapping<(AccountId, AccountId), Balance>,
+    }
+
+    impl Contract {
+        /// Mint the total supply to the caller
+        #[ink(constructor)]
+        pub fn new(total_supply: Balance) -> Self {
+            let mut instance = Self::default();
+            instance.total_supply = total_supply;
+            instance.balances.insert(instance.env().caller(), &total_supply);
+            instance
+        }
+
+        fn inner_transfer(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let from_balance = self
+                .balance_of(from)
+                .checked_sub(value)
+                .ok_or(PSP22Error::InsufficientBalance)?;
+            self.balances.insert(from, &from_balance);
+            let to_balance = self.balance_of(to);
+            self.balances.insert(to, &to_balance.saturating_add(value));
+            Ok(())
+        }
+    }
+
+    impl PSP22 for Contract {
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.total_supply
+        }
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((owner, spender)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, _data: Vec<u8>) -> Result<(), PSP22Error> {
+            let from = self.env().caller();
+            self.inner_transfer(from, to, value)
+        }
+
+        #[ink(message)]
+        fn transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            _data: Vec<u8>,
+        ) -> Result<(), PSP22Error> {
+            let spender = self.env().caller();
+            let allowance = self
+                .allowance(from, spender)
+                .checked_sub(value)
+                .ok_or(PSP22Error::InsufficientAllowance)?;
+            self.inner_transfer(from, to, value)?;
+            self.allowances.insert((from, spender), &allowance);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn approve(&mut self, spender: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let owner = self.env().caller();
+            self.allowances.insert((owner, spender), &value);
+            Ok(())
+        }
+    }
+}
@@ -1,5 +1,48 @@
 use inkv5_client_lib::traits::access_control::AccessControlError;
 use inkv5_client_lib::traits::RollupClientError;
+use crate::traits::reward::{PSP22Error, RewardError};
+use crate::traits::timelock::TimelockError;
+
+/// Base of the error codes returned by the reward manager contract
+const REWARD_ERROR_CODE_BASE: u128 = 1_000;
+/// Base of the error codes returned by the dAppStaking developer contract
+const DAPPS_STAKING_DEVELOPER_ERROR_CODE_BASE: u128 = 2_000;
+
+/// Errors returned by the dAppStaking developer contract
+#[derive(Debug, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum DappsStakingDeveloperError {
+    AccessControlError(AccessControlError),
+    TransferError,
+    UpgradeError,
+    TimelockError(TimelockError),
+}
+
+impl DappsStakingDeveloperError {
+    /// Stable code of the error, it must not be changed when a variant is added
+    pub fn code(&self) -> u128 {
+        match self {
+            DappsStakingDeveloperError::AccessControlError(_) => 1,
+            DappsStakingDeveloperError::TransferError => 2,
+            DappsStakingDeveloperError::UpgradeError => 3,
+            DappsStakingDeveloperError::TimelockError(_) => 4,
+        }
+    }
+}
+
+/// convertor from AccessControlError to DappsStakingDeveloperError
+impl From<AccessControlError> for DappsStakingDeveloperError {
+    fn from(error: AccessControlError) -> Self {
+        DappsStakingDeveloperError::AccessControlError(error)
+    }
+}
+
+/// convertor from TimelockError to DappsStakingDeveloperError
+impl From<TimelockError> for DappsStakingDeveloperError {
+    fn from(error: TimelockError) -> Self {
+        DappsStakingDeveloperError::TimelockError(error)
+    }
+}
 
 #[derive(Debug, Eq, PartialEq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -18,11 +61,99 @@ pub enum RaffleError {
     RollupClientError(RollupClientError),
     FailedToDecode,
     TryFromIntError,
-    CrossContractCallError1,
-    CrossContractCallError2,
+    /// the cross contract call failed in the environment (callee trapped, not found, ...)
+    CrossContractCallEnvError,
+    /// the callee failed to dispatch the message (unknown selector, ...)
+    CrossContractCallLangError,
     TransferError,
     DappsStakingDeveloperAddressMissing,
     RewardManagerAddressMissing,
+    SubOverFlow,
+    /// error returned by the PSP22 token used for the rewards
+    PSP22Error(PSP22Error),
+    AlreadySubmitted,
+    NoPendingRaffle,
+    DisputeWindowNotOver,
+    DisputeWindowOver,
+    MissingWinnersProof,
+    InvalidWinnersProof,
+    ExcludedWinner,
+    WinnersMismatch,
+    MissingRandomCommitment,
+    MissingSnapshotCommitment,
+    UpgradeError,
+    AlreadyMigrated,
+    TimelockError(TimelockError),
+    Paused,
+    /// error returned by the reward manager contract
+    RewardError(RewardError),
+    /// error returned by the dAppStaking developer contract
+    DappsStakingDeveloperError(DappsStakingDeveloperError),
+    TreasuryAddressMissing,
+    ProtocolFeeTooHigh,
+    TooManyErasSkipped,
+    InvalidDomain,
+    MissingDomain,
+    AttestorExpired,
+    QuorumThresholdTooHigh,
+    UnknownPool,
+}
+
+impl RaffleError {
+    /// Stable code of the error sent back to the worker.
+    /// The errors returned by the reward manager contract are in the range 1_000 - 1_999,
+    /// the ones returned by the dAppStaking developer contract in the range 2_000 - 2_999.
+    /// The codes must not be changed when a variant is added
+    pub fn code(&self) -> u128 {
+        match self {
+            RaffleError::IncorrectEra => 1,
+            RaffleError::NoReward => 2,
+            RaffleError::NoRatioSet => 3,
+            RaffleError::IncorrectRatio => 4,
+            RaffleError::NoWinner => 5,
+            RaffleError::TooManyWinners => 6,
+            RaffleError::DivByZero => 7,
+            RaffleError::MulOverFlow => 8,
+            RaffleError::AddOverFlow => 9,
+            RaffleError::AccessControlError(_) => 10,
+            RaffleError::RollupClientError(_) => 11,
+            RaffleError::FailedToDecode => 12,
+            RaffleError::TryFromIntError => 13,
+            RaffleError::CrossContractCallEnvError => 14,
+            RaffleError::CrossContractCallLangError => 15,
+            RaffleError::TransferError => 16,
+            RaffleError::DappsStakingDeveloperAddressMissing => 17,
+            RaffleError::RewardManagerAddressMissing => 18,
+            RaffleError::SubOverFlow => 19,
+            RaffleError::AlreadySubmitted => 20,
+            RaffleError::NoPendingRaffle => 21,
+            RaffleError::DisputeWindowNotOver => 22,
+            RaffleError::DisputeWindowOver => 23,
+            RaffleError::MissingWinnersProof => 24,
+            RaffleError::InvalidWinnersProof => 25,
+            RaffleError::ExcludedWinner => 26,
+            RaffleError::WinnersMismatch => 27,
+            RaffleError::UpgradeError => 28,
+            RaffleError::AlreadyMigrated => 29,
+            RaffleError::TimelockError(_) => 30,
+            RaffleError::Paused => 31,
+            RaffleError::RewardError(e) => REWARD_ERROR_CODE_BASE.saturating_add(e.code()),
+            RaffleError::DappsStakingDeveloperError(e) => {
+                DAPPS_STAKING_DEVELOPER_ERROR_CODE_BASE.saturating_add(e.code())
+            }
+            RaffleError::PSP22Error(_) => 32,
+            RaffleError::MissingRandomCommitment => 33,
+            RaffleError::MissingSnapshotCommitment => 34,
+            RaffleError::TreasuryAddressMissing => 35,
+            RaffleError::ProtocolFeeTooHigh => 36,
+            RaffleError::TooManyErasSkipped => 37,
+            RaffleError::InvalidDomain => 38,
+            RaffleError::MissingDomain => 39,
+            RaffleError::AttestorExpired => 40,
+            RaffleError::QuorumThresholdTooHigh => 41,
+            RaffleError::UnknownPool => 42,
+        }
+    }
 }
 
 /// convertor from AccessControlError to ParticipantFilterError
@@ -32,6 +163,13 @@ impl From<AccessControlError> for RaffleError {
     }
 }
 
+/// convertor from TimelockError to RaffleError
+impl From<TimelockError> for RaffleError {
+    fn from(error: TimelockError) -> Self {
+        RaffleError::TimelockError(error)
+    }
+}
+
 /// convertor from RollupClientError to ContractError
 impl From<RollupClientError> for RaffleError {
     fn from(error: RollupClientError) -> Self {
@@ -39,11 +177,36 @@ impl From<RollupClientError> for RaffleError {
     }
 }
 
-/// convertor from ContractError to RollupClientError
+/// convertor from RewardError to RaffleError
+impl From<RewardError> for RaffleError {
+    fn from(error: RewardError) -> Self {
+        RaffleError::RewardError(error)
+    }
+}
+
+/// convertor from PSP22Error to RaffleError
+impl From<PSP22Error> for RaffleError {
+    fn from(error: PSP22Error) -> Self {
+        RaffleError::PSP22Error(error)
+    }
+}
+
+/// convertor from DappsStakingDeveloperError to RaffleError
+impl From<DappsStakingDeveloperError> for RaffleError {
+    fn from(error: DappsStakingDeveloperError) -> Self {
+        RaffleError::DappsStakingDeveloperError(error)
+    }
+}
+
+/// convertor from RaffleError to RollupClientError
+/// The error is sent back with its stable code so the worker can identify it
 impl From<RaffleError> for RollupClientError {
     fn from(error: RaffleError) -> Self {
-        ink::env::debug_println!("Error: {:?}", error);
-        RollupClientError::UnsupportedAction
+        match error {
+            // keep the error raised by the rollup client
+            RaffleError::RollupClientError(e) => e,
+            _ => RollupClientError::BusinessError(error.code()),
+        }
     }
 }
 
@@ -5,5 +5,9 @@ pub type Balance = u128;
 
 pub mod error;
 pub mod participant_filter;
+pub mod pausable;
+pub mod pool;
 pub mod raffle;
-pub mod reward;
\ No newline at end of file
+pub mod reward;
+pub mod timelock;
+pub mod winner_proof;
\ No newline at end of file
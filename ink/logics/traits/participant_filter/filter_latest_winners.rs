@@ -1,8 +1,10 @@
 use crate::traits::RAFFLE_MANAGER_ROLE;
+use crate::traits::pool::{PoolId, PoolKey, DEFAULT_POOL};
 use crate::traits::error::RaffleError;
 use ink::prelude::collections::vec_deque::VecDeque;
 use ink::prelude::vec::Vec;
 use ink::primitives::AccountId;
+use ink::storage::{Lazy, Mapping};
 use inkv5_client_lib::traits::access_control::{BaseAccessControl};
 use inkv5_client_lib::traits::kv_store::KvStore;
 use ink::env::DefaultEnvironment;
@@ -11,12 +13,43 @@ use ink::scale::{Encode};
 
 const LAST_WINNERS: u32 = ink::selector_id!("LAST_WINNER");
 
+/// Event emitted when the number of filtered winners is updated
+#[ink::event]
+pub struct NbWinnersFilteredUpdated {
+    #[ink(topic)]
+    pool_id: PoolId,
+    nb_filtered_winners: u16,
+}
+
+/// State of the winners filter of a pool, restored when the result of a raffle is cancelled
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct WinnersFilterSnapshot {
+    pub last_winners: Vec<AccountId>,
+    /// winners in cooldown and the last era they won
+    pub winners_in_cooldown: Vec<(AccountId, u32)>,
+}
+
 #[derive(Default, Debug)]
 #[ink::storage_item]
 pub struct FilterLatestWinnersData {
     nb_filtered_winners: u16,
     /// last winners to exclude
     last_winners: VecDeque<AccountId>,
+    /// number of eras a winner must wait before participating again.
+    /// If zero, the last `nb_filtered_winners` winners are excluded
+    cooldown_eras: Lazy<u32>,
+    /// last era won by account (and by pool)
+    last_won_eras: Mapping<PoolKey<AccountId>, u32>,
+    /// winners still in cooldown by pool
+    winners_in_cooldown: Mapping<PoolId, Vec<AccountId>>,
+    /// last winners to exclude for the pools other than the default one
+    pool_last_winners: Mapping<PoolId, VecDeque<AccountId>>,
+    /// number of filtered winners for the pools other than the default one
+    pool_nb_filtered_winners: Mapping<PoolId, u16>,
+    /// cooldown for the pools other than the default one
+    pool_cooldown_eras: Mapping<PoolId, u32>,
 }
 
 #[ink::trait_definition]
@@ -38,6 +71,39 @@ pub trait FilterLatestWinners {
         &mut self,
         winner: AccountId,
     ) -> Result<(), RaffleError> ;
+
+    /// Set the number of eras a winner must wait before participating again.
+    /// Zero means the last `nb_filtered_winners` winners are excluded instead
+    #[ink(message)]
+    fn set_cooldown_eras(&mut self, cooldown_eras: u32) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_cooldown_eras(&self) -> u32;
+
+    /// Return true if the account cannot participate in the raffle for the given era
+    #[ink(message)]
+    fn is_in_cooldown(&self, account: AccountId, era: u32) -> bool;
+
+    /// Return the addresses excluded from the next raffle of the given pool
+    #[ink(message)]
+    fn get_pool_last_winners(&self, pool_id: PoolId) -> Vec<AccountId>;
+
+    #[ink(message)]
+    fn set_pool_nb_winners_filtered(
+        &mut self,
+        pool_id: PoolId,
+        nb_filtered_winners: u16,
+    ) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_nb_winners_filtered(&self, pool_id: PoolId) -> u16;
+
+    /// Set the cooldown of the given pool
+    #[ink(message)]
+    fn set_pool_cooldown_eras(&mut self, pool_id: PoolId, cooldown_eras: u32) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_cooldown_eras(&self, pool_id: PoolId) -> u32;
 }
 
 
@@ -51,50 +117,277 @@ pub trait BaseFilterLatestWinners: FilterLatestWinnersStorage + KvStore + BaseAc
     
     fn inner_set_nb_winners_filtered(
         &mut self,
+        pool_id: PoolId,
         nb_filtered_winners: u16,
     ) -> Result<(), RaffleError> {
 
         let caller = ::ink::env::caller::<DefaultEnvironment>();
         self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
 
-        FilterLatestWinnersStorage::get_mut_storage(self).nb_filtered_winners = nb_filtered_winners;
+        if pool_id == DEFAULT_POOL {
+            FilterLatestWinnersStorage::get_mut_storage(self).nb_filtered_winners = nb_filtered_winners;
+        } else {
+            FilterLatestWinnersStorage::get_mut_storage(self)
+                .pool_nb_filtered_winners
+                .insert(pool_id, &nb_filtered_winners);
+        }
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, NbWinnersFilteredUpdated>(
+            NbWinnersFilteredUpdated{pool_id, nb_filtered_winners}
+        );
         Ok(())
     }
     
-    fn inner_get_nb_winners_filtered(&self) -> u16 {
-        FilterLatestWinnersStorage::get_storage(self).nb_filtered_winners
+    fn inner_get_nb_winners_filtered(&self, pool_id: PoolId) -> u16 {
+        if pool_id == DEFAULT_POOL {
+            return FilterLatestWinnersStorage::get_storage(self).nb_filtered_winners;
+        }
+        FilterLatestWinnersStorage::get_storage(self)
+            .pool_nb_filtered_winners
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    /// Set the cooldown of the pool and keep excluded the addresses already excluded.
+    /// `next_era` is the next era of the pool.
+    fn inner_set_cooldown_eras(
+        &mut self,
+        pool_id: PoolId,
+        cooldown_eras: u32,
+        next_era: u32,
+    ) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
+
+        let previous_cooldown_eras = self.inner_get_cooldown_eras(pool_id);
+        if pool_id == DEFAULT_POOL {
+            FilterLatestWinnersStorage::get_mut_storage(self).cooldown_eras.set(&cooldown_eras);
+        } else {
+            FilterLatestWinnersStorage::get_mut_storage(self)
+                .pool_cooldown_eras
+                .insert(pool_id, &cooldown_eras);
+        }
+
+        if previous_cooldown_eras == 0 && cooldown_eras != 0 {
+            // the last winners wait as if they won the last era
+            let last_era = next_era.saturating_sub(1);
+            let mut winners_in_cooldown = self.get_winners_in_cooldown(pool_id);
+            for winner in self.get_last_winners_queue(pool_id) {
+                if !winners_in_cooldown.contains(&winner) {
+                    FilterLatestWinnersStorage::get_mut_storage(self)
+                        .last_won_eras
+                        .insert(PoolKey::new(pool_id, winner), &last_era);
+                    winners_in_cooldown.push(winner);
+                }
+            }
+            self.set_winners_in_cooldown(pool_id, winners_in_cooldown);
+        } else if previous_cooldown_eras != 0 && cooldown_eras == 0 {
+            // the last winners, kept up to date during the cooldown, are excluded again
+            for winner in self.get_winners_in_cooldown(pool_id) {
+                FilterLatestWinnersStorage::get_mut_storage(self)
+                    .last_won_eras
+                    .remove(PoolKey::new(pool_id, winner));
+            }
+            self.set_winners_in_cooldown(pool_id, Vec::new());
+        }
+        // remove the winners no longer in cooldown and
+        // save the excluded addresses in the kv store
+        self.update_winners_in_cooldown(pool_id, next_era);
+        self.save_excluded_addresses(pool_id);
+        Ok(())
     }
 
-    fn add_winner(&mut self, winner: AccountId) {
-        // add the last winner in the back
-        FilterLatestWinnersStorage::get_mut_storage(self).last_winners.push_back(winner);
-        if FilterLatestWinnersStorage::get_storage(self).last_winners.len() > FilterLatestWinnersStorage::get_storage(self).nb_filtered_winners as usize
+    fn inner_get_cooldown_eras(&self, pool_id: PoolId) -> u32 {
+        if pool_id == DEFAULT_POOL {
+            return FilterLatestWinnersStorage::get_storage(self)
+                .cooldown_eras
+                .get()
+                .unwrap_or_default();
+        }
+        FilterLatestWinnersStorage::get_storage(self)
+            .pool_cooldown_eras
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    fn inner_is_in_cooldown(&self, pool_id: PoolId, account: AccountId, era: u32) -> bool {
+        let cooldown_eras = self.inner_get_cooldown_eras(pool_id);
+        if cooldown_eras == 0 {
+            return false;
+        }
+        match FilterLatestWinnersStorage::get_storage(self)
+            .last_won_eras
+            .get(PoolKey::new(pool_id, account))
         {
-            // remove the oldest winner (from the front)
-            FilterLatestWinnersStorage::get_mut_storage(self).last_winners.pop_front();
+            Some(last_won_era) => era >= last_won_era && era - last_won_era <= cooldown_eras,
+            _ => false,
+        }
+    }
+
+    /// Remove the winners who are no longer in cooldown for the next era of the pool.
+    /// The winners of an era after the next one (ie when the next era is moved back) are kept.
+    fn update_winners_in_cooldown(&mut self, pool_id: PoolId, next_era: u32) {
+        let cooldown_eras = self.inner_get_cooldown_eras(pool_id);
+        if cooldown_eras == 0 {
+            return;
+        }
+        let winners = self.get_winners_in_cooldown(pool_id);
+        let mut winners_in_cooldown = Vec::with_capacity(winners.len());
+        for winner in winners {
+            let key = PoolKey::new(pool_id, winner);
+            let last_won_era = FilterLatestWinnersStorage::get_storage(self).last_won_eras.get(&key);
+            match last_won_era {
+                Some(last_won_era) if next_era.saturating_sub(last_won_era) <= cooldown_eras => {
+                    winners_in_cooldown.push(winner);
+                }
+                _ => FilterLatestWinnersStorage::get_mut_storage(self).last_won_eras.remove(&key),
+            }
         }
+        self.set_winners_in_cooldown(pool_id, winners_in_cooldown);
         // save the excluded addresses in the kv store
-        let excluded_addresses = Vec::from(FilterLatestWinnersStorage::get_storage(self).last_winners.clone());
+        self.save_excluded_addresses(pool_id);
+    }
+
+    fn get_winners_in_cooldown(&self, pool_id: PoolId) -> Vec<AccountId> {
+        FilterLatestWinnersStorage::get_storage(self)
+            .winners_in_cooldown
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    fn set_winners_in_cooldown(&mut self, pool_id: PoolId, winners: Vec<AccountId>) {
+        FilterLatestWinnersStorage::get_mut_storage(self)
+            .winners_in_cooldown
+            .insert(pool_id, &winners);
+    }
+
+    fn get_last_winners_queue(&self, pool_id: PoolId) -> VecDeque<AccountId> {
+        if pool_id == DEFAULT_POOL {
+            return FilterLatestWinnersStorage::get_storage(self).last_winners.clone();
+        }
+        FilterLatestWinnersStorage::get_storage(self)
+            .pool_last_winners
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    fn set_last_winners_queue(&mut self, pool_id: PoolId, last_winners: VecDeque<AccountId>) {
+        if pool_id == DEFAULT_POOL {
+            FilterLatestWinnersStorage::get_mut_storage(self).last_winners = last_winners;
+        } else {
+            FilterLatestWinnersStorage::get_mut_storage(self)
+                .pool_last_winners
+                .insert(pool_id, &last_winners);
+        }
+    }
+
+    /// Save the addresses excluded from the next raffle of the pool in the kv store
+    fn save_excluded_addresses(&mut self, pool_id: PoolId) {
+        let excluded_addresses = self.inner_get_last_winners(pool_id);
         KvStore::inner_set_value(
             self,
-            &LAST_WINNERS.encode(),
+            &PoolKey::new(pool_id, LAST_WINNERS).encode(),
             Some(&excluded_addresses.encode()),
         );
     }
 
-    fn inner_get_last_winners(&self) -> Vec<AccountId> {
-        Vec::from(FilterLatestWinnersStorage::get_storage(self).last_winners.clone())
+    fn add_winner(&mut self, pool_id: PoolId, winner: AccountId, era: u32) {
+        self.add_winners(pool_id, &[winner], era);
+    }
+
+    /// Add the winners of the era, the excluded addresses are refreshed once all the winners are added
+    fn add_winners(&mut self, pool_id: PoolId, winners: &[AccountId], era: u32) {
+        // add the last winners in the back.
+        // The queue is also kept up to date during the cooldown to switch back without losing the last winners
+        let nb_filtered_winners = self.inner_get_nb_winners_filtered(pool_id) as usize;
+        let mut last_winners = self.get_last_winners_queue(pool_id);
+        for winner in winners {
+            last_winners.push_back(*winner);
+            if last_winners.len() > nb_filtered_winners {
+                // remove the oldest winner (from the front)
+                last_winners.pop_front();
+            }
+        }
+        self.set_last_winners_queue(pool_id, last_winners);
+
+        if self.inner_get_cooldown_eras(pool_id) != 0 {
+            // the winners must wait before participating again
+            let mut winners_in_cooldown = self.get_winners_in_cooldown(pool_id);
+            for winner in winners {
+                FilterLatestWinnersStorage::get_mut_storage(self)
+                    .last_won_eras
+                    .insert(PoolKey::new(pool_id, *winner), &era);
+                if !winners_in_cooldown.contains(winner) {
+                    winners_in_cooldown.push(*winner);
+                }
+            }
+            self.set_winners_in_cooldown(pool_id, winners_in_cooldown);
+            self.update_winners_in_cooldown(pool_id, era.saturating_add(1));
+            return;
+        }
+
+        // save the excluded addresses in the kv store
+        self.save_excluded_addresses(pool_id);
+    }
+
+    /// Return the current state of the winners filter of the pool
+    fn snapshot_winners_filter(&self, pool_id: PoolId) -> WinnersFilterSnapshot {
+        let mut winners_in_cooldown = Vec::new();
+        for winner in self.get_winners_in_cooldown(pool_id) {
+            if let Some(last_won_era) = FilterLatestWinnersStorage::get_storage(self)
+                .last_won_eras
+                .get(PoolKey::new(pool_id, winner))
+            {
+                winners_in_cooldown.push((winner, last_won_era));
+            }
+        }
+        WinnersFilterSnapshot {
+            last_winners: Vec::from(self.get_last_winners_queue(pool_id)),
+            winners_in_cooldown,
+        }
+    }
+
+    /// Restore the state of the winners filter of the pool
+    fn restore_winners_filter(&mut self, pool_id: PoolId, snapshot: WinnersFilterSnapshot) {
+        for winner in self.get_winners_in_cooldown(pool_id) {
+            FilterLatestWinnersStorage::get_mut_storage(self)
+                .last_won_eras
+                .remove(PoolKey::new(pool_id, winner));
+        }
+        let mut winners_in_cooldown = Vec::with_capacity(snapshot.winners_in_cooldown.len());
+        for (winner, last_won_era) in snapshot.winners_in_cooldown {
+            FilterLatestWinnersStorage::get_mut_storage(self)
+                .last_won_eras
+                .insert(PoolKey::new(pool_id, winner), &last_won_era);
+            winners_in_cooldown.push(winner);
+        }
+        self.set_winners_in_cooldown(pool_id, winners_in_cooldown);
+        self.set_last_winners_queue(pool_id, VecDeque::from(snapshot.last_winners));
+        // save the excluded addresses in the kv store
+        self.save_excluded_addresses(pool_id);
+    }
+
+    /// Return the addresses excluded from the next raffle of the pool
+    fn inner_get_last_winners(&self, pool_id: PoolId) -> Vec<AccountId> {
+        if self.inner_get_cooldown_eras(pool_id) != 0 {
+            return self.get_winners_in_cooldown(pool_id);
+        }
+        Vec::from(self.get_last_winners_queue(pool_id))
     }
 
+    /// Add the address as if it won the raffle of the pool for the given era
     fn inner_add_address_in_last_winner(
         &mut self,
+        pool_id: PoolId,
         winner: AccountId,
+        era: u32,
     ) -> Result<(), RaffleError> {
         
         let caller = ::ink::env::caller::<DefaultEnvironment>();
         self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
         
-        self.add_winner(winner);
+        self.add_winner(pool_id, winner, era);
         Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,96 @@
+use ink::env::DefaultEnvironment;
+use ink::primitives::AccountId;
+use ink::storage::Lazy;
+use inkv5_client_lib::traits::access_control::{AccessControlError, BaseAccessControl, RoleType, ADMIN_ROLE};
+
+/// Role allowed to pause the contract
+pub const GUARDIAN_ROLE: RoleType = ink::selector_id!("GUARDIAN");
+
+#[derive(Debug, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PausableError {
+    AlreadyPaused,
+    NotPaused,
+    AccessControlError(AccessControlError),
+}
+
+/// convertor from AccessControlError to PausableError
+impl From<AccessControlError> for PausableError {
+    fn from(error: AccessControlError) -> Self {
+        PausableError::AccessControlError(error)
+    }
+}
+
+/// The data are not stored in the root of the contract,
+/// so the component can be added in a contract already deployed
+#[derive(Default, Debug)]
+#[ink::storage_item]
+pub struct PausableData {
+    paused: Lazy<bool>,
+}
+
+/// Event emitted when the contract is paused
+#[ink::event]
+pub struct Paused {
+    account: AccountId,
+}
+
+/// Event emitted when the contract is unpaused
+#[ink::event]
+pub struct Unpaused {
+    account: AccountId,
+}
+
+#[ink::trait_definition]
+pub trait Pausable {
+    /// Pause the contract, only the guardian can pause it
+    #[ink(message)]
+    fn pause(&mut self) -> Result<(), PausableError>;
+
+    /// Unpause the contract, only the admin can unpause it
+    #[ink(message)]
+    fn unpause(&mut self) -> Result<(), PausableError>;
+
+    #[ink(message)]
+    fn is_paused(&self) -> bool;
+}
+
+pub trait PausableStorage {
+    fn get_storage(&self) -> &PausableData;
+    fn get_mut_storage(&mut self) -> &mut PausableData;
+}
+
+pub trait BasePausable: PausableStorage + BaseAccessControl {
+
+    fn inner_is_paused(&self) -> bool {
+        PausableStorage::get_storage(self).paused.get().unwrap_or_default()
+    }
+
+    fn inner_pause(&mut self) -> Result<(), PausableError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(GUARDIAN_ROLE, caller)?;
+
+        if self.inner_is_paused() {
+            return Err(PausableError::AlreadyPaused);
+        }
+        PausableStorage::get_mut_storage(self).paused.set(&true);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, Paused>(Paused { account: caller });
+        Ok(())
+    }
+
+    fn inner_unpause(&mut self) -> Result<(), PausableError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        if !self.inner_is_paused() {
+            return Err(PausableError::NotPaused);
+        }
+        PausableStorage::get_mut_storage(self).paused.set(&false);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, Unpaused>(Unpaused { account: caller });
+        Ok(())
+    }
+}
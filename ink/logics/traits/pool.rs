@@ -0,0 +1,45 @@
+use ink::scale::{Encode, EncodeLike, Output};
+
+/// Identifier of a pool: a dApp with its own developer contract, ratio distribution,
+/// winners filter and era cursor
+pub type PoolId = u32;
+
+/// Pool existing before the pools were introduced.
+/// Its data are stored in the same keys as before
+pub const DEFAULT_POOL: PoolId = 0;
+
+/// Key of the data stored by pool (kv store or mapping).
+/// For the default pool, the key is encoded without the pool id,
+/// so that the data written before the pools were introduced are still read.
+/// For the other pools, the pool id is appended to the key.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[ink::scale_derive(TypeInfo)]
+pub struct PoolKey<K> {
+    pub pool_id: PoolId,
+    pub key: K,
+}
+
+impl<K> PoolKey<K> {
+    pub fn new(pool_id: PoolId, key: K) -> Self {
+        Self { pool_id, key }
+    }
+}
+
+impl<K: Encode> Encode for PoolKey<K> {
+    fn size_hint(&self) -> usize {
+        if self.pool_id == DEFAULT_POOL {
+            self.key.size_hint()
+        } else {
+            self.key.size_hint() + self.pool_id.size_hint()
+        }
+    }
+
+    fn encode_to<T: Output + ?Sized>(&self, dest: &mut T) {
+        self.key.encode_to(dest);
+        if self.pool_id != DEFAULT_POOL {
+            self.pool_id.encode_to(dest);
+        }
+    }
+}
+
+impl<K: Encode> EncodeLike for PoolKey<K> {}
@@ -1,14 +1,127 @@
 use crate::traits::error::RaffleError;
+use crate::traits::pool::{PoolId, PoolKey, DEFAULT_POOL};
+use crate::traits::winner_proof::{random_commitment, verify_winners, HashValue, WinnersProof};
 use crate::traits::{Balance, RAFFLE_MANAGER_ROLE};
 use ink::prelude::vec::Vec;
 use ink::primitives::AccountId;
-use inkv5_client_lib::traits::access_control::{BaseAccessControl};
+use ink::storage::{Lazy, Mapping};
+use inkv5_client_lib::traits::access_control::{BaseAccessControl, ADMIN_ROLE};
 use inkv5_client_lib::traits::kv_store::KvStore;
 use ink::env::DefaultEnvironment;
 use ink::scale::{Decode, Encode};
 
 const NEXT_ERA: u32 = ink::selector_id!("NEXT_ERA");
 const NB_WINNERS: u32 = ink::selector_id!("NB_WINNERS");
+const JACKPOT: u32 = ink::selector_id!("JACKPOT");
+const PROTOCOL_FEE: u32 = ink::selector_id!("PROTOCOL_FEE");
+const LAST_COMMITTED_ERA: u32 = ink::selector_id!("LAST_COMMITTED_ERA");
+
+/// Base used for the share of the jackpot released in a raffle (ie basis points)
+pub const JACKPOT_RATIO_BASE: u16 = 10_000;
+
+/// Base used for the protocol fee (ie basis points)
+pub const PROTOCOL_FEE_BASE: u16 = 10_000;
+
+/// Max protocol fee taken on the rewards of the winners (10%)
+pub const MAX_PROTOCOL_FEE: u16 = 1_000;
+
+/// Max number of eras skipped at once by `skip_eras`
+pub const MAX_SKIPPED_ERAS: u32 = 100;
+
+/// The random value used to draw the winners of an era is committed in the response
+/// of the era `RANDOM_COMMITMENT_ERA_OFFSET` eras before, ie before the participants are known
+pub const RANDOM_COMMITMENT_ERA_OFFSET: u32 = 2;
+
+/// The snapshot of the participants of an era is committed in the response of the previous era:
+/// the stakes of an era are known at the end of the previous one
+pub const SNAPSHOT_COMMITMENT_ERA_OFFSET: u32 = 1;
+
+/// Max number of results returned by `get_raffle_results`
+pub const MAX_RAFFLE_RESULTS: u32 = 100;
+
+/// Result of the raffle for a given era
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct RaffleRecord {
+    pub era: u32,
+    pub skipped: bool,
+    pub total_rewards: Balance,
+    /// winners and the rewards by winner, after the protocol fee
+    pub winners: Vec<(AccountId, Balance)>,
+    /// fee taken on the rewards of the winners
+    pub protocol_fee: Balance,
+    /// block number when the raffle has been done or skipped
+    pub block_number: u32,
+}
+
+/// Destination of the remainder not distributed to the winners (rounding dust and unused ratios).
+/// When the jackpot is enabled, the remainder is always added in the jackpot
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub enum RemainderDestination {
+    /// the remainder is left in the dAppStaking developer contract
+    #[default]
+    DappsStakingDeveloper,
+    /// the remainder is sent to the treasury
+    Treasury,
+    /// the remainder is given to the first-ranked winner
+    FirstWinner,
+}
+
+/// Reason why the raffle is skipped for an era
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub enum SkipReason {
+    /// no raffle during the Voting subperiod
+    VotingSubPeriod,
+    NoParticipant,
+    NoReward,
+    /// decided by the operator, ie when the worker was down
+    OperatorDecision,
+}
+
+/// Event emitted when the ratio distribution is updated
+#[ink::event]
+pub struct RatioDistributionUpdated {
+    pool_id: PoolId,
+    ratio: Vec<Balance>,
+    total_ratio: Balance,
+}
+
+/// Event emitted when the destination of the remainder is updated
+#[ink::event]
+pub struct RemainderDestinationUpdated {
+    #[ink(topic)]
+    pool_id: PoolId,
+    destination: RemainderDestination,
+}
+
+/// Event emitted when the protocol fee is updated
+#[ink::event]
+pub struct ProtocolFeeUpdated {
+    #[ink(topic)]
+    pool_id: PoolId,
+    protocol_fee: u16,
+}
+
+/// Event emitted when a range of eras is skipped by the admin
+#[ink::event]
+pub struct ErasSkipped {
+    pool_id: PoolId,
+    from_era: u32,
+    to_era: u32,
+    reason: SkipReason,
+}
+
+/// Event emitted when the next era is set by the raffle manager
+#[ink::event]
+pub struct NextEraUpdated {
+    pool_id: PoolId,
+    next_era: u32,
+}
 
 #[derive(Default, Debug)]
 #[ink::storage_item]
@@ -16,6 +129,34 @@ pub struct RaffleData {
     ratio_distribution: Vec<Balance>,
     total_ratio_distribution: Balance,
     last_era_done: u32,
+    /// result of the raffle by era (and by pool)
+    results: Mapping<PoolKey<u32>, RaffleRecord>,
+    /// rewards not distributed (skipped eras and remainder) when the jackpot is enabled, by pool
+    jackpots: Mapping<PoolId, Balance>,
+    jackpot_enabled: Lazy<bool>,
+    /// share of the jackpot added to the rewards of the next raffle (base JACKPOT_RATIO_BASE)
+    jackpot_release_ratio: Lazy<u16>,
+    /// if true, the winners must come with the proof they have been drawn
+    winners_proof_required: Lazy<bool>,
+    /// commitment on the random value and the block it has been received, by era (and by pool)
+    random_commitments: Mapping<PoolKey<u32>, (HashValue, u32)>,
+    /// commitment on the snapshot of the participants (root of the merkle tree)
+    /// and the block it has been received, by era (and by pool)
+    snapshot_commitments: Mapping<PoolKey<u32>, (HashValue, u32)>,
+    /// destination of the rewards not distributed when the jackpot is disabled
+    remainder_destination: Lazy<RemainderDestination>,
+    /// fee taken on the rewards of the winners (base PROTOCOL_FEE_BASE)
+    protocol_fee: Lazy<u16>,
+    /// reason why the raffle has been skipped by era (and by pool)
+    skip_reasons: Mapping<PoolKey<u32>, SkipReason>,
+    /// ratio distribution and total ratio for the pools other than the default one
+    pool_ratio_distributions: Mapping<PoolId, (Vec<Balance>, Balance)>,
+    /// jackpot enabled and release ratio for the pools other than the default one
+    pool_jackpot_configs: Mapping<PoolId, (bool, u16)>,
+    /// protocol fee for the pools other than the default one
+    pool_protocol_fees: Mapping<PoolId, u16>,
+    /// destination of the remainder for the pools other than the default one
+    pool_remainder_destinations: Mapping<PoolId, RemainderDestination>,
 }
 
 #[ink::trait_definition]
@@ -47,6 +188,143 @@ pub trait Raffle {
     //#[openbrush::modifiers(access_control::only_role(RAFFLE_MANAGER_ROLE))]
     fn set_next_era(&mut self, next_era: u32) -> Result<(), RaffleError>;
 
+    /// Enable or disable the jackpot.
+    /// When the jackpot is enabled, the rewards of skipped eras and the rewards not distributed
+    /// to the winners are added in the jackpot.
+    /// For each raffle, jackpot * release_ratio / JACKPOT_RATIO_BASE is added to the winners rewards
+    #[ink(message)]
+    fn set_jackpot_config(
+        &mut self,
+        enabled: bool,
+        release_ratio: u16,
+    ) -> Result<(), RaffleError>;
+
+    /// Return true if the jackpot is enabled and the share of the jackpot released in each raffle
+    #[ink(message)]
+    fn get_jackpot_config(&self) -> (bool, u16);
+
+    /// Set where the rewards not distributed to the winners go when the jackpot is disabled
+    #[ink(message)]
+    fn set_remainder_destination(
+        &mut self,
+        destination: RemainderDestination,
+    ) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_remainder_destination(&self) -> RemainderDestination;
+
+    /// Set the fee taken on the rewards of the winners and sent to the treasury.
+    /// The fee is in basis points and cannot exceed MAX_PROTOCOL_FEE
+    #[ink(message)]
+    fn set_protocol_fee(&mut self, protocol_fee: u16) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_protocol_fee(&self) -> u16;
+
+    #[ink(message)]
+    fn get_jackpot(&self) -> Balance;
+
+    /// If true, the raffle result must contain the proof the winners have been drawn among the participants
+    #[ink(message)]
+    fn set_winners_proof_required(&mut self, required: bool) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn is_winners_proof_required(&self) -> bool;
+
+    /// Return the result of the raffle for the given era
+    #[ink(message)]
+    fn get_raffle_result(&self, era: u32) -> Option<RaffleRecord>;
+
+    /// Return the results of the raffles, starting from the given era.
+    /// At most `limit` eras are read (capped to MAX_RAFFLE_RESULTS) and the eras without result are ignored.
+    #[ink(message)]
+    fn get_raffle_results(&self, from_era: u32, limit: u32) -> Vec<RaffleRecord>;
+
+    /// Return the reason why the raffle has been skipped for the given era
+    #[ink(message)]
+    fn get_skip_reason(&self, era: u32) -> Option<SkipReason>;
+
+    /// Skip the raffles from the era `from_era` (must be the next era) to the era `to_era` (included).
+    /// At most MAX_SKIPPED_ERAS eras can be skipped at once
+    #[ink(message)]
+    fn skip_eras(&mut self, from_era: u32, to_era: u32, reason: SkipReason) -> Result<(), RaffleError>;
+
+    /// Return the rewards the winners would receive for the given rewards
+    /// and the remainder not distributed, without saving the result
+    #[ink(message)]
+    fn preview_distribution(
+        &self,
+        total_rewards: Balance,
+        winners: Vec<AccountId>,
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError>;
+
+    /// Set the rate sharing by the winners of the given pool.
+    /// The messages without pool id apply to the default pool
+    #[ink(message)]
+    fn set_pool_ratio_distribution(
+        &mut self,
+        pool_id: PoolId,
+        ratio: Vec<Balance>,
+        total_ratio: Balance,
+    ) -> Result<(), RaffleError>;
+
+    /// Return the ratio distribution and the total ratio of the given pool
+    #[ink(message)]
+    fn get_pool_ratio_distribution(&self, pool_id: PoolId) -> (Vec<Balance>, Balance);
+
+    #[ink(message)]
+    fn get_pool_next_era(&self, pool_id: PoolId) -> Result<u32, RaffleError>;
+
+    #[ink(message)]
+    fn set_pool_next_era(&mut self, pool_id: PoolId, next_era: u32) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_jackpot(&self, pool_id: PoolId) -> Balance;
+
+    /// Return the result of the raffle of the given pool for the given era
+    #[ink(message)]
+    fn get_pool_raffle_result(&self, pool_id: PoolId, era: u32) -> Option<RaffleRecord>;
+
+    /// Enable or disable the jackpot of the given pool
+    #[ink(message)]
+    fn set_pool_jackpot_config(
+        &mut self,
+        pool_id: PoolId,
+        enabled: bool,
+        release_ratio: u16,
+    ) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_jackpot_config(&self, pool_id: PoolId) -> (bool, u16);
+
+    /// Set the fee taken on the rewards of the winners of the given pool
+    #[ink(message)]
+    fn set_pool_protocol_fee(&mut self, pool_id: PoolId, protocol_fee: u16) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_protocol_fee(&self, pool_id: PoolId) -> u16;
+
+    /// Set where the rewards not distributed to the winners of the given pool go when its jackpot is disabled
+    #[ink(message)]
+    fn set_pool_remainder_destination(
+        &mut self,
+        pool_id: PoolId,
+        destination: RemainderDestination,
+    ) -> Result<(), RaffleError>;
+
+    #[ink(message)]
+    fn get_pool_remainder_destination(&self, pool_id: PoolId) -> RemainderDestination;
+
+    /// Return the rewards the winners of the given pool would receive for the given rewards
+    /// and the remainder not distributed, without saving the result
+    #[ink(message)]
+    fn preview_pool_distribution(
+        &self,
+        pool_id: PoolId,
+        total_rewards: Balance,
+        winners: Vec<AccountId>,
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError>;
+
 }
 
 pub trait RaffleStorage {
@@ -64,6 +342,7 @@ pub trait BaseRaffle: RaffleStorage + KvStore + BaseAccessControl {
     /// Sum(ratio[i]) <= total_ratio. Otherwise teh error IncorrectRatio is expected
     fn inner_set_ratio_distribution(
         &mut self,
+        pool_id: PoolId,
         ratio: Vec<Balance>,
         total_ratio: Balance,
     ) -> Result<(), RaffleError> {
@@ -79,67 +358,496 @@ pub trait BaseRaffle: RaffleStorage + KvStore + BaseAccessControl {
             return Err(RaffleError::IncorrectRatio);
         }
 
-        RaffleStorage::get_mut_storage(self).ratio_distribution = ratio;
-        RaffleStorage::get_mut_storage(self).total_ratio_distribution = total_ratio;
+        if pool_id == DEFAULT_POOL {
+            RaffleStorage::get_mut_storage(self).ratio_distribution = ratio.clone();
+            RaffleStorage::get_mut_storage(self).total_ratio_distribution = total_ratio;
+        } else {
+            RaffleStorage::get_mut_storage(self)
+                .pool_ratio_distributions
+                .insert(pool_id, &(ratio.clone(), total_ratio));
+        }
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, RatioDistributionUpdated>(
+            RatioDistributionUpdated{pool_id, ratio, total_ratio}
+        );
 
         // save the NB WINNERS in the kv store
-        let nb_winners: u16 = u16::try_from(RaffleStorage::get_storage(self).ratio_distribution.len())?;
-        KvStore::inner_set_value(self, &NB_WINNERS.encode(), Some(&nb_winners.encode()));
+        self.save_nb_winners(pool_id)
+    }
 
+    /// Save the number of winners of the pool in the kv store
+    fn save_nb_winners(&mut self, pool_id: PoolId) -> Result<(), RaffleError> {
+        let nb_winners: u16 = u16::try_from(self.inner_get_ratio_distribution(pool_id).len())?;
+        KvStore::inner_set_value(
+            self,
+            &PoolKey::new(pool_id, NB_WINNERS).encode(),
+            Some(&nb_winners.encode()),
+        );
         Ok(())
     }
 
-    fn inner_get_ratio_distribution(&self) -> Vec<Balance> {
-        let ratio = &RaffleStorage::get_storage(self).ratio_distribution;
-        ratio.to_vec()
+    /// Return the ratio distribution and the total ratio of the pool
+    fn inner_get_pool_ratio_distribution(&self, pool_id: PoolId) -> (Vec<Balance>, Balance) {
+        if pool_id == DEFAULT_POOL {
+            let data = RaffleStorage::get_storage(self);
+            return (data.ratio_distribution.to_vec(), data.total_ratio_distribution);
+        }
+        RaffleStorage::get_storage(self)
+            .pool_ratio_distributions
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    fn inner_get_ratio_distribution(&self, pool_id: PoolId) -> Vec<Balance> {
+        self.inner_get_pool_ratio_distribution(pool_id).0
     }
 
-    fn inner_get_total_ratio_distribution(&self) -> Balance {
-        RaffleStorage::get_storage(self).total_ratio_distribution
+    fn inner_get_total_ratio_distribution(&self, pool_id: PoolId) -> Balance {
+        self.inner_get_pool_ratio_distribution(pool_id).1
     }
 
-    fn inner_get_next_era(&self) -> Result<u32, RaffleError> {
-        match KvStore::inner_get_value(self, &NEXT_ERA.encode()) {
+    fn inner_get_next_era(&self, pool_id: PoolId) -> Result<u32, RaffleError> {
+        match KvStore::inner_get_value(self, &PoolKey::new(pool_id, NEXT_ERA).encode()) {
             Some(v) => u32::decode(&mut v.as_slice()).map_err(|_| RaffleError::FailedToDecode),
             _ => Ok(0),
         }
     }
 
-    fn inner_set_next_era(&mut self, next_era: u32) -> Result<(), RaffleError> {
+    fn inner_set_next_era(&mut self, pool_id: PoolId, next_era: u32) -> Result<(), RaffleError> {
         let caller = ::ink::env::caller::<DefaultEnvironment>();
         self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
 
-        self.inner_set_next_era_unchecked(next_era)
+        self.inner_set_next_era_unchecked(pool_id, next_era)?;
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, NextEraUpdated>(
+            NextEraUpdated{pool_id, next_era}
+        );
+        Ok(())
     }
 
-    fn inner_set_next_era_unchecked(&mut self, next_era: u32) -> Result<(), RaffleError> {
-        KvStore::inner_set_value(self, &NEXT_ERA.encode(), Some(&next_era.encode()));
+    fn inner_set_next_era_unchecked(&mut self, pool_id: PoolId, next_era: u32) -> Result<(), RaffleError> {
+        KvStore::inner_set_value(
+            self,
+            &PoolKey::new(pool_id, NEXT_ERA).encode(),
+            Some(&next_era.encode()),
+        );
         Ok(())
     }
 
-    fn skip_raffle(&mut self, era: u32) -> Result<(), RaffleError> {
-        // check if the raffle has not been done
-        if self.inner_get_next_era()? != era {
-            return Err(RaffleError::IncorrectEra);
+    fn inner_set_jackpot_config(
+        &mut self,
+        pool_id: PoolId,
+        enabled: bool,
+        release_ratio: u16,
+    ) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
+
+        if release_ratio > JACKPOT_RATIO_BASE {
+            return Err(RaffleError::IncorrectRatio);
         }
 
-        // set the raffle is done or skipped
-        self.inner_set_next_era_unchecked(era.checked_add(1).ok_or(RaffleError::AddOverFlow)?)?;
+        if pool_id == DEFAULT_POOL {
+            RaffleStorage::get_mut_storage(self).jackpot_enabled.set(&enabled);
+            RaffleStorage::get_mut_storage(self).jackpot_release_ratio.set(&release_ratio);
+        } else {
+            RaffleStorage::get_mut_storage(self)
+                .pool_jackpot_configs
+                .insert(pool_id, &(enabled, release_ratio));
+        }
+        Ok(())
+    }
+
+    fn inner_get_jackpot_config(&self, pool_id: PoolId) -> (bool, u16) {
+        let data = RaffleStorage::get_storage(self);
+        if pool_id == DEFAULT_POOL {
+            return (
+                data.jackpot_enabled.get().unwrap_or_default(),
+                data.jackpot_release_ratio.get().unwrap_or_default(),
+            );
+        }
+        data.pool_jackpot_configs.get(pool_id).unwrap_or_default()
+    }
 
+    fn is_jackpot_enabled(&self, pool_id: PoolId) -> bool {
+        self.inner_get_jackpot_config(pool_id).0
+    }
+
+    fn inner_set_remainder_destination(
+        &mut self,
+        pool_id: PoolId,
+        destination: RemainderDestination,
+    ) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        if pool_id == DEFAULT_POOL {
+            RaffleStorage::get_mut_storage(self).remainder_destination.set(&destination);
+        } else {
+            RaffleStorage::get_mut_storage(self)
+                .pool_remainder_destinations
+                .insert(pool_id, &destination);
+        }
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, RemainderDestinationUpdated>(
+            RemainderDestinationUpdated{pool_id, destination}
+        );
         Ok(())
     }
 
-    fn mark_raffle_done(
+    fn inner_get_remainder_destination(&self, pool_id: PoolId) -> RemainderDestination {
+        if pool_id == DEFAULT_POOL {
+            return RaffleStorage::get_storage(self).remainder_destination.get().unwrap_or_default();
+        }
+        RaffleStorage::get_storage(self)
+            .pool_remainder_destinations
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    /// Return where the remainder of a raffle of the pool goes, None when it is added in the jackpot
+    fn get_effective_remainder_destination(&self, pool_id: PoolId) -> Option<RemainderDestination> {
+        if self.is_jackpot_enabled(pool_id) {
+            None
+        } else {
+            Some(self.inner_get_remainder_destination(pool_id))
+        }
+    }
+
+    fn inner_set_protocol_fee(&mut self, pool_id: PoolId, protocol_fee: u16) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        if protocol_fee > MAX_PROTOCOL_FEE {
+            return Err(RaffleError::ProtocolFeeTooHigh);
+        }
+
+        if pool_id == DEFAULT_POOL {
+            RaffleStorage::get_mut_storage(self).protocol_fee.set(&protocol_fee);
+        } else {
+            RaffleStorage::get_mut_storage(self)
+                .pool_protocol_fees
+                .insert(pool_id, &protocol_fee);
+        }
+        self.save_protocol_fee(pool_id);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, ProtocolFeeUpdated>(
+            ProtocolFeeUpdated{pool_id, protocol_fee}
+        );
+        Ok(())
+    }
+
+    fn inner_get_protocol_fee(&self, pool_id: PoolId) -> u16 {
+        if pool_id == DEFAULT_POOL {
+            return RaffleStorage::get_storage(self).protocol_fee.get().unwrap_or_default();
+        }
+        RaffleStorage::get_storage(self)
+            .pool_protocol_fees
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    /// Save the protocol fee of the pool in the kv store
+    fn save_protocol_fee(&mut self, pool_id: PoolId) {
+        let protocol_fee = self.inner_get_protocol_fee(pool_id);
+        KvStore::inner_set_value(
+            self,
+            &PoolKey::new(pool_id, PROTOCOL_FEE).encode(),
+            Some(&protocol_fee.encode()),
+        );
+    }
+
+    /// Deduct the protocol fee of the pool from the rewards of each winner.
+    /// Return the rewards by winner after the fee and the total fee
+    fn take_protocol_fee(
+        &self,
+        pool_id: PoolId,
+        winners_rewards: Vec<(AccountId, Balance)>,
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError> {
+        let protocol_fee = Balance::from(self.inner_get_protocol_fee(pool_id));
+        if protocol_fee == 0 {
+            return Ok((winners_rewards, 0));
+        }
+
+        let mut total_fee: Balance = 0;
+        let mut net_rewards = Vec::with_capacity(winners_rewards.len());
+        for (account, amount) in winners_rewards {
+            let fee = amount
+                .checked_mul(protocol_fee)
+                .ok_or(RaffleError::MulOverFlow)?
+                .checked_div(Balance::from(PROTOCOL_FEE_BASE))
+                .ok_or(RaffleError::DivByZero)?;
+            total_fee = total_fee.checked_add(fee).ok_or(RaffleError::AddOverFlow)?;
+            net_rewards.push((
+                account,
+                amount.checked_sub(fee).ok_or(RaffleError::SubOverFlow)?,
+            ));
+        }
+        Ok((net_rewards, total_fee))
+    }
+
+    fn inner_get_jackpot(&self, pool_id: PoolId) -> Balance {
+        RaffleStorage::get_storage(self)
+            .jackpots
+            .get(pool_id)
+            .unwrap_or_default()
+    }
+
+    fn set_jackpot(&mut self, pool_id: PoolId, jackpot: Balance) {
+        RaffleStorage::get_mut_storage(self).jackpots.insert(pool_id, &jackpot);
+        // save the jackpot in the kv store
+        KvStore::inner_set_value(
+            self,
+            &PoolKey::new(pool_id, JACKPOT).encode(),
+            Some(&jackpot.encode()),
+        );
+    }
+
+    fn inner_set_winners_proof_required(&mut self, required: bool) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(RAFFLE_MANAGER_ROLE, caller)?;
+
+        RaffleStorage::get_mut_storage(self).winners_proof_required.set(&required);
+        Ok(())
+    }
+
+    fn inner_is_winners_proof_required(&self) -> bool {
+        RaffleStorage::get_storage(self)
+            .winners_proof_required
+            .get()
+            .unwrap_or_default()
+    }
+
+    /// Save the commitment on the random value used to draw the winners of the era.
+    /// A commitment cannot be replaced
+    fn inner_commit_random(&mut self, pool_id: PoolId, era: u32, commitment: HashValue) {
+        let key = PoolKey::new(pool_id, era);
+        let random_commitments = &mut RaffleStorage::get_mut_storage(self).random_commitments;
+        if !random_commitments.contains(key) {
+            let block_number = ::ink::env::block_number::<DefaultEnvironment>();
+            random_commitments.insert(key, &(commitment, block_number));
+        }
+        self.save_last_committed_era(pool_id, era);
+    }
+
+    /// Save the root of the snapshot of the participants used to draw the winners of the era.
+    /// A commitment cannot be replaced
+    fn inner_commit_snapshot(&mut self, pool_id: PoolId, era: u32, snapshot_root: HashValue) {
+        let key = PoolKey::new(pool_id, era);
+        let snapshot_commitments = &mut RaffleStorage::get_mut_storage(self).snapshot_commitments;
+        if !snapshot_commitments.contains(key) {
+            let block_number = ::ink::env::block_number::<DefaultEnvironment>();
+            snapshot_commitments.insert(key, &(snapshot_root, block_number));
+        }
+        self.save_last_committed_era(pool_id, era);
+    }
+
+    fn inner_get_snapshot_commitment(&self, pool_id: PoolId, era: u32) -> Option<HashValue> {
+        RaffleStorage::get_storage(self)
+            .snapshot_commitments
+            .get(PoolKey::new(pool_id, era))
+            .map(|(snapshot_root, _)| snapshot_root)
+    }
+
+    /// Save in the kv store the era of the pool whose random value and snapshot are both committed,
+    /// so that the worker knows it can send the proof of the winners of this era
+    fn save_last_committed_era(&mut self, pool_id: PoolId, era: u32) {
+        let key = PoolKey::new(pool_id, era);
+        let data = RaffleStorage::get_storage(self);
+        if data.random_commitments.contains(key) && data.snapshot_commitments.contains(key) {
+            KvStore::inner_set_value(
+                self,
+                &PoolKey::new(pool_id, LAST_COMMITTED_ERA).encode(),
+                Some(&era.encode()),
+            );
+        }
+    }
+
+    fn inner_get_random_commitment(&self, pool_id: PoolId, era: u32) -> Option<HashValue> {
+        RaffleStorage::get_storage(self)
+            .random_commitments
+            .get(PoolKey::new(pool_id, era))
+            .map(|(commitment, _)| commitment)
+    }
+
+    /// Check the random value used in the proof has been committed in a previous block.
+    /// The commitment is mandatory when a proof is supplied.
+    /// It is kept so that the raffle can be run again if its result is cancelled
+    fn check_random_commitment(
+        &self,
+        pool_id: PoolId,
+        era: u32,
+        random: &HashValue,
+    ) -> Result<(), RaffleError> {
+        let (commitment, block_number) = RaffleStorage::get_storage(self)
+            .random_commitments
+            .get(PoolKey::new(pool_id, era))
+            .ok_or(RaffleError::MissingRandomCommitment)?;
+        if random_commitment(random) != commitment
+            || block_number >= ::ink::env::block_number::<DefaultEnvironment>()
+        {
+            return Err(RaffleError::InvalidWinnersProof);
+        }
+        Ok(())
+    }
+
+    /// Check the snapshot used in the proof has been committed in a previous block.
+    /// The commitment is mandatory when a proof is supplied
+    fn check_snapshot_commitment(
+        &self,
+        pool_id: PoolId,
+        era: u32,
+        snapshot_root: &HashValue,
+    ) -> Result<(), RaffleError> {
+        let (commitment, block_number) = RaffleStorage::get_storage(self)
+            .snapshot_commitments
+            .get(PoolKey::new(pool_id, era))
+            .ok_or(RaffleError::MissingSnapshotCommitment)?;
+        if *snapshot_root != commitment
+            || block_number >= ::ink::env::block_number::<DefaultEnvironment>()
+        {
+            return Err(RaffleError::InvalidWinnersProof);
+        }
+        Ok(())
+    }
+
+    /// Return true if the account cannot win the raffle of the pool.
+    /// By default, nobody is excluded
+    fn is_excluded_winner(&self, _pool_id: PoolId, _account: &AccountId) -> bool {
+        false
+    }
+
+    fn inner_get_raffle_result(&self, pool_id: PoolId, era: u32) -> Option<RaffleRecord> {
+        RaffleStorage::get_storage(self).results.get(PoolKey::new(pool_id, era))
+    }
+
+    fn inner_get_raffle_results(&self, pool_id: PoolId, from_era: u32, limit: u32) -> Vec<RaffleRecord> {
+        let limit = limit.min(MAX_RAFFLE_RESULTS);
+        let mut results = Vec::new();
+        for i in 0..limit {
+            let Some(era) = from_era.checked_add(i) else {
+                break;
+            };
+            if let Some(result) = self.inner_get_raffle_result(pool_id, era) {
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    fn save_raffle_result(
         &mut self,
+        pool_id: PoolId,
         era: u32,
+        skipped: bool,
         total_rewards: Balance,
-        winners: &[AccountId],
-    ) -> Result<Vec<(AccountId, Balance)>, RaffleError> {
+        winners: Vec<(AccountId, Balance)>,
+        protocol_fee: Balance,
+    ) {
+        let result = RaffleRecord {
+            era,
+            skipped,
+            total_rewards,
+            winners,
+            protocol_fee,
+            block_number: ::ink::env::block_number::<DefaultEnvironment>(),
+        };
+        RaffleStorage::get_mut_storage(self)
+            .results
+            .insert(PoolKey::new(pool_id, era), &result);
+    }
+
+    fn remove_raffle_result(&mut self, pool_id: PoolId, era: u32) {
+        RaffleStorage::get_mut_storage(self).results.remove(PoolKey::new(pool_id, era));
+    }
+
+    fn inner_get_skip_reason(&self, pool_id: PoolId, era: u32) -> Option<SkipReason> {
+        RaffleStorage::get_storage(self).skip_reasons.get(PoolKey::new(pool_id, era))
+    }
+
+    fn save_skip_reason(&mut self, pool_id: PoolId, era: u32, reason: SkipReason) {
+        RaffleStorage::get_mut_storage(self)
+            .skip_reasons
+            .insert(PoolKey::new(pool_id, era), &reason);
+    }
+
+    fn skip_raffle(
+        &mut self,
+        pool_id: PoolId,
+        era: u32,
+        rewards: Balance,
+        reason: SkipReason,
+    ) -> Result<(), RaffleError> {
         // check if the raffle has not been done
-        if self.inner_get_next_era()? != era {
+        if self.inner_get_next_era(pool_id)? != era {
             return Err(RaffleError::IncorrectEra);
         }
 
+        // the rewards of this era are kept for the next raffles
+        if self.is_jackpot_enabled(pool_id) && rewards > 0 {
+            let jackpot = self
+                .inner_get_jackpot(pool_id)
+                .checked_add(rewards)
+                .ok_or(RaffleError::AddOverFlow)?;
+            self.set_jackpot(pool_id, jackpot);
+        }
+
+        // save the result
+        self.save_raffle_result(pool_id, era, true, rewards, Vec::new(), 0);
+        self.save_skip_reason(pool_id, era, reason);
+
+        // set the raffle is done or skipped
+        self.inner_set_next_era_unchecked(pool_id, era.checked_add(1).ok_or(RaffleError::AddOverFlow)?)?;
+
+        Ok(())
+    }
+
+    fn inner_skip_eras(
+        &mut self,
+        pool_id: PoolId,
+        from_era: u32,
+        to_era: u32,
+        reason: SkipReason,
+    ) -> Result<(), RaffleError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        // the range must start from the next era
+        if self.inner_get_next_era(pool_id)? != from_era || from_era > to_era {
+            return Err(RaffleError::IncorrectEra);
+        }
+        if to_era.checked_sub(from_era).ok_or(RaffleError::SubOverFlow)? >= MAX_SKIPPED_ERAS {
+            return Err(RaffleError::TooManyErasSkipped);
+        }
+
+        for era in from_era..=to_era {
+            self.save_raffle_result(pool_id, era, true, 0, Vec::new(), 0);
+            self.save_skip_reason(pool_id, era, reason);
+        }
+
+        // set the raffles are skipped
+        self.inner_set_next_era_unchecked(pool_id, to_era.checked_add(1).ok_or(RaffleError::AddOverFlow)?)?;
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, ErasSkipped>(
+            ErasSkipped{pool_id, from_era, to_era, reason}
+        );
+        Ok(())
+    }
+
+    /// Compute the rewards of the winners based on the ratio distribution and the released jackpot of the pool.
+    /// Return the rewards by winner and the remainder not distributed by the ratios.
+    /// If the remainder is given to the first winner, it is already included in his rewards
+    fn compute_distribution(
+        &self,
+        pool_id: PoolId,
+        total_rewards: Balance,
+        winners: &[AccountId],
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError> {
         if total_rewards == 0 {
             // no reward
             return Err(RaffleError::NoReward);
@@ -152,7 +860,9 @@ pub trait BaseRaffle: RaffleStorage + KvStore + BaseAccessControl {
             return Err(RaffleError::NoWinner);
         }
 
-        let nb_ratio = RaffleStorage::get_storage(self).ratio_distribution.len();
+        let (ratio_distribution, total_ratio_distribution) =
+            self.inner_get_pool_ratio_distribution(pool_id);
+        let nb_ratio = ratio_distribution.len();
 
         if nb_ratio == 0 {
             // no ration set
@@ -164,26 +874,154 @@ pub trait BaseRaffle: RaffleStorage + KvStore + BaseAccessControl {
             return Err(RaffleError::TooManyWinners);
         }
 
+        // share of the jackpot added to the rewards of the winners
+        let (jackpot_enabled, jackpot_release_ratio) = self.inner_get_jackpot_config(pool_id);
+        let jackpot = self.inner_get_jackpot(pool_id);
+        let released_jackpot = if jackpot_enabled {
+            jackpot
+                .checked_mul(Balance::from(jackpot_release_ratio))
+                .ok_or(RaffleError::MulOverFlow)?
+                .checked_div(Balance::from(JACKPOT_RATIO_BASE))
+                .ok_or(RaffleError::DivByZero)?
+        } else {
+            0
+        };
+
+        // the released jackpot is shared between the winners based on their ratio
+        let mut winners_ratio: Balance = 0;
+        for i in 0..nb_winners {
+            let ratio = ratio_distribution.get(i).unwrap_or(&0);
+            winners_ratio = winners_ratio.checked_add(*ratio).ok_or(RaffleError::AddOverFlow)?;
+        }
+
         let mut winners_and_rewards = Vec::with_capacity(nb_winners);
+        let mut distributed_rewards: Balance = 0;
 
         for (i, winner) in winners.iter().enumerate() {
             // select the rewards ratio
-            let ratio = RaffleStorage::get_storage(self).ratio_distribution.get(i).unwrap_or(&0);
+            let ratio = ratio_distribution.get(i).unwrap_or(&0);
             if *ratio != 0 {
                 // compute the reward for this winner based on the ratio
-                let amount = total_rewards
+                let mut amount = total_rewards
                     .checked_mul(*ratio)
                     .ok_or(RaffleError::MulOverFlow)?
-                    .checked_div(RaffleStorage::get_storage(self).total_ratio_distribution)
+                    .checked_div(total_ratio_distribution)
                     .ok_or(RaffleError::DivByZero)?;
+                if released_jackpot > 0 {
+                    // add the share of the jackpot for this winner
+                    let jackpot_amount = released_jackpot
+                        .checked_mul(*ratio)
+                        .ok_or(RaffleError::MulOverFlow)?
+                        .checked_div(winners_ratio)
+                        .ok_or(RaffleError::DivByZero)?;
+                    amount = amount.checked_add(jackpot_amount).ok_or(RaffleError::AddOverFlow)?;
+                }
+                distributed_rewards = distributed_rewards
+                    .checked_add(amount)
+                    .ok_or(RaffleError::AddOverFlow)?;
                 // add the pending rewards for this account
                 winners_and_rewards.push((*winner, amount));
             }
         }
 
+        // the rewards and the released jackpot not distributed
+        let remainder = total_rewards
+            .checked_add(released_jackpot)
+            .ok_or(RaffleError::AddOverFlow)?
+            .checked_sub(distributed_rewards)
+            .ok_or(RaffleError::SubOverFlow)?;
+
+        if remainder > 0
+            && self.get_effective_remainder_destination(pool_id) == Some(RemainderDestination::FirstWinner)
+        {
+            let first_winner = winners.first().ok_or(RaffleError::NoWinner)?;
+            // the first-ranked winner has no reward yet when its ratio is zero
+            match winners_and_rewards.first_mut() {
+                Some((account, amount)) if account == first_winner => {
+                    *amount = amount.checked_add(remainder).ok_or(RaffleError::AddOverFlow)?;
+                }
+                _ => winners_and_rewards.insert(0, (*first_winner, remainder)),
+            }
+        }
+
+        Ok((winners_and_rewards, remainder))
+    }
+
+    fn inner_preview_distribution(
+        &self,
+        pool_id: PoolId,
+        total_rewards: Balance,
+        winners: Vec<AccountId>,
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance), RaffleError> {
+        self.compute_distribution(pool_id, total_rewards, &winners)
+    }
+
+    /// Save the result of the raffle of the pool and return the rewards by winner after the protocol fee,
+    /// the remainder and the protocol fee
+    fn mark_raffle_done(
+        &mut self,
+        pool_id: PoolId,
+        era: u32,
+        total_rewards: Balance,
+        winners: &[AccountId],
+        winners_proof: Option<&WinnersProof>,
+    ) -> Result<(Vec<(AccountId, Balance)>, Balance, Balance), RaffleError> {
+        // check if the raffle has not been done
+        if self.inner_get_next_era(pool_id)? != era {
+            return Err(RaffleError::IncorrectEra);
+        }
+
+        // check the winners have been drawn among the participants
+        match winners_proof {
+            Some(winners_proof) => {
+                self.check_random_commitment(pool_id, era, &winners_proof.random)?;
+                self.check_snapshot_commitment(pool_id, era, &winners_proof.snapshot_root)?;
+                verify_winners(era, winners, winners_proof, |account| {
+                    self.is_excluded_winner(pool_id, account)
+                })?;
+            }
+            _ => {
+                if self.inner_is_winners_proof_required() {
+                    return Err(RaffleError::MissingWinnersProof);
+                }
+            }
+        }
+
+        let (winners_and_rewards, remainder) = self.compute_distribution(pool_id, total_rewards, winners)?;
+
+        if self.is_jackpot_enabled(pool_id) {
+            let mut distributed_rewards: Balance = 0;
+            for (_, amount) in &winners_and_rewards {
+                distributed_rewards = distributed_rewards
+                    .checked_add(*amount)
+                    .ok_or(RaffleError::AddOverFlow)?;
+            }
+            // the rewards not distributed are added in the jackpot
+            let new_jackpot = self
+                .inner_get_jackpot(pool_id)
+                .checked_add(total_rewards)
+                .ok_or(RaffleError::AddOverFlow)?
+                .checked_sub(distributed_rewards)
+                .ok_or(RaffleError::SubOverFlow)?;
+            self.set_jackpot(pool_id, new_jackpot);
+        }
+
+        // the protocol fee is taken on the rewards of the winners
+        let (winners_and_rewards, protocol_fee) = self.take_protocol_fee(pool_id, winners_and_rewards)?;
+
+        // save the result
+        self.save_raffle_result(
+            pool_id,
+            era,
+            false,
+            total_rewards,
+            winners_and_rewards.clone(),
+            protocol_fee,
+        );
+
         // set the raffle is done
-        self.inner_set_next_era_unchecked(era.checked_add(1).ok_or(RaffleError::AddOverFlow)?)?;
+        self.inner_set_next_era_unchecked(pool_id, era.checked_add(1).ok_or(RaffleError::AddOverFlow)?)?;
 
-        Ok(winners_and_rewards)
+        Ok((winners_and_rewards, remainder, protocol_fee))
     }
 }
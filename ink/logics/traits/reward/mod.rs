@@ -1,7 +1,20 @@
 pub mod psp22_reward;
 
+use ink::prelude::string::String;
 use inkv5_client_lib::traits::access_control::AccessControlError;
 
+/// Errors returned by a PSP22 token contract
+#[derive(Debug, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum PSP22Error {
+    Custom(String),
+    InsufficientBalance,
+    InsufficientAllowance,
+    ZeroRecipientAddress,
+    ZeroSenderAddress,
+    SafeTransferCheckFailed(String),
+}
+
 #[derive(Debug, Eq, PartialEq)]
 #[ink::scale_derive(Encode, Decode, TypeInfo)]
 #[allow(clippy::cast_possible_truncation)]
@@ -11,6 +24,37 @@ pub enum RewardError {
     AddOverFlow,
     NoReward,
     AccessControlError(AccessControlError),
+    PSP22Error(PSP22Error),
+    CrossContractCallError,
+    UnexpectedTransferredValue,
+    ClaimWindowNotSet,
+    ExpiredRewardsDestinationMissing,
+    RewardsExpired,
+    SubOverFlow,
+    Paused,
+    NotClaimOperator,
+}
+
+impl RewardError {
+    /// Stable code of the error, it must not be changed when a variant is added
+    pub fn code(&self) -> u128 {
+        match self {
+            RewardError::InsufficientTransferredBalance => 1,
+            RewardError::TransferError => 2,
+            RewardError::AddOverFlow => 3,
+            RewardError::NoReward => 4,
+            RewardError::AccessControlError(_) => 5,
+            RewardError::PSP22Error(_) => 6,
+            RewardError::CrossContractCallError => 7,
+            RewardError::UnexpectedTransferredValue => 8,
+            RewardError::ClaimWindowNotSet => 9,
+            RewardError::ExpiredRewardsDestinationMissing => 10,
+            RewardError::RewardsExpired => 11,
+            RewardError::SubOverFlow => 12,
+            RewardError::Paused => 13,
+            RewardError::NotClaimOperator => 14,
+        }
+    }
 }
 
 /// convertor from AccessControlError to ParticipantFilterError
@@ -18,4 +62,11 @@ impl From<AccessControlError> for RewardError {
     fn from(error: AccessControlError) -> Self {
         RewardError::AccessControlError(error)
     }
+}
+
+/// convertor from PSP22Error to RewardError
+impl From<PSP22Error> for RewardError {
+    fn from(error: PSP22Error) -> Self {
+        RewardError::PSP22Error(error)
+    }
 }
\ No newline at end of file
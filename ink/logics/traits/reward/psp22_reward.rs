@@ -1,6 +1,7 @@
-use crate::traits::reward::RewardError;
+use crate::traits::reward::{PSP22Error, RewardError};
+use ink::env::call::{build_call, ExecutionInput, Selector};
 use ink::prelude::vec::Vec;
-use ink::storage::Mapping;
+use ink::storage::{Lazy, Mapping};
 use ink::primitives::AccountId;
 use inkv5_client_lib::traits::access_control::{BaseAccessControl};
 use ink::env::DefaultEnvironment;
@@ -13,11 +14,69 @@ pub type Psp22RewardRef = dyn Psp22Reward;
 pub const REWARD_MANAGER_ROLE: u32 = ink::selector_id!("REWARD_MANAGER");
 pub const REWARD_VIEWER_ROLE: u32 = ink::selector_id!("REWARD_VIEWER");
 
+// Selector of PSP22::transfer: "0xdb20f9f5"
+const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+// Selector of PSP22::transfer_from: "0x54b3c76e"
+const PSP22_TRANSFER_FROM_SELECTOR: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
+// Selector of PSP22::balance_of: "0x6568382f"
+const PSP22_BALANCE_OF_SELECTOR: [u8; 4] = [0x65, 0x68, 0x38, 0x2f];
+
+/// Max number of claims kept in the history of an account
+pub const MAX_CLAIMS_HISTORY: u32 = 50;
+
+/// Rewards claimed by an account
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct ClaimRecord {
+    /// block number when the rewards have been claimed
+    pub block_number: u32,
+    pub amount: Balance,
+    /// account which received the rewards
+    pub destination: AccountId,
+    /// eras and the rewards by era making up the amount.
+    /// The rewards funded before the ledger by era was introduced are not listed
+    pub eras: Vec<(u32, Balance)>,
+}
+
+/// Balance of the contract compared to the rewards owed to the winners
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct Solvency {
+    /// balance of the contract, in native currency or in PSP22 token
+    pub balance: Balance,
+    /// sum of the pending rewards
+    pub total_pending: Balance,
+    /// balance above the pending rewards
+    pub surplus: Balance,
+}
 
 #[derive(Default, Debug)]
 #[ink::storage_item]
 pub struct Psp22RewardData {
     pending_rewards: Mapping<AccountId, Balance>,
+    /// PSP22 token used for the rewards. If None, the rewards are paid in native currency
+    token: Lazy<Option<AccountId>>,
+    /// last era funded
+    current_era: Lazy<u32>,
+    /// number of eras the winners have to claim the rewards of an era. If zero, the rewards never expire.
+    /// The window is counted from the last era funded, not from the current era of dAppStaking,
+    /// and the rewards pending before they were recorded by era never expire
+    claim_window_eras: Lazy<u32>,
+    /// destination of the rewards not claimed in time (ie the treasury or the raffle pot)
+    expired_rewards_destination: Lazy<Option<AccountId>>,
+    /// sum of the pending rewards for all accounts
+    total_pending: Lazy<Balance>,
+    /// rewards not claimed yet by era and by account
+    pending_rewards_by_era: Mapping<(u32, AccountId), Balance>,
+    /// eras with rewards not claimed yet by account
+    pending_eras: Mapping<AccountId, Vec<u32>>,
+    /// last claims by account (at most MAX_CLAIMS_HISTORY)
+    claims: Mapping<AccountId, Vec<ClaimRecord>>,
+    /// address receiving the rewards of the account, if it is not the account itself
+    payout_addresses: Mapping<AccountId, AccountId>,
+    /// account authorized to claim the rewards on behalf of the account
+    claim_operators: Mapping<AccountId, AccountId>,
 }
 
 
@@ -38,6 +97,41 @@ pub struct RewardsClaimed {
     amount: Balance,
 }
 
+/// Event emitted when the rewards claimed are sent to the payout address of the account
+#[ink::event]
+pub struct RewardsPaidOut {
+    #[ink(topic)]
+    account: AccountId,
+    #[ink(topic)]
+    payout_address: AccountId,
+    amount: Balance,
+}
+
+/// Event emitted when an account sets or removes its payout address
+#[ink::event]
+pub struct PayoutAddressUpdated {
+    #[ink(topic)]
+    account: AccountId,
+    payout_address: Option<AccountId>,
+}
+
+/// Event emitted when an account authorizes or removes its claim operator
+#[ink::event]
+pub struct ClaimOperatorUpdated {
+    #[ink(topic)]
+    account: AccountId,
+    operator: Option<AccountId>,
+}
+
+/// Event emitted when the rewards of a user have not been claimed in time
+#[ink::event]
+pub struct RewardExpired {
+    #[ink(topic)]
+    account: AccountId,
+    amount: Balance,
+    destination: AccountId,
+}
+
 
 #[ink::trait_definition]
 pub trait Psp22Reward {
@@ -50,6 +144,14 @@ pub trait Psp22Reward {
         accounts: Vec<(AccountId, Balance)>,
     ) -> Result<(), RewardError> ;
 
+    /// Add the winners of several eras and fund their rewards
+    /// rewards contains the era, the winner and the rewards of the winner for this era
+    #[ink(message, payable, selector = 0xef51d0aa)]
+    fn fund_rewards_by_era(
+        &mut self,
+        rewards: Vec<(u32, AccountId, Balance)>,
+    ) -> Result<(), RewardError> ;
+
     /// return true if the current account has pending rewards
     #[ink(message)]
     fn has_pending_rewards(&self) -> bool ;
@@ -71,10 +173,62 @@ pub trait Psp22Reward {
     fn claim(&mut self) -> Result<(), RewardError>;
 
     /// claim all pending rewards for the given account
+    /// The rewards are sent to the payout address of the account if it is set, to the account otherwise
     /// After claiming, there is not anymore pending rewards for this account
     #[ink(message)]
     fn claim_from(&mut self, from: AccountId) -> Result<(), RewardError> ;
 
+    /// claim all pending rewards for the given account
+    /// The rewards are sent to the payout address of the account if it is set, to the account otherwise
+    /// Only the account itself or its claim operator can call it
+    #[ink(message)]
+    fn claim_for(&mut self, from: AccountId) -> Result<(), RewardError> ;
+
+    /// set the address receiving the rewards of the current account, None to receive them directly
+    #[ink(message)]
+    fn set_payout_address(&mut self, payout_address: Option<AccountId>) -> Result<(), RewardError> ;
+
+    #[ink(message)]
+    fn get_payout_address(&self, account: AccountId) -> Option<AccountId> ;
+
+    /// authorize the operator to claim the rewards of the current account, None to remove it
+    #[ink(message)]
+    fn set_claim_operator(&mut self, operator: Option<AccountId>) -> Result<(), RewardError> ;
+
+    #[ink(message)]
+    fn get_claim_operator(&self, account: AccountId) -> Option<AccountId> ;
+
+    /// return the PSP22 token used for the rewards, None if the rewards are paid in native currency
+    #[ink(message)]
+    fn get_reward_token(&self) -> Option<AccountId> ;
+
+    /// return the number of eras the winners have to claim the rewards of an era (zero if no expiry)
+    #[ink(message)]
+    fn get_claim_window_eras(&self) -> u32 ;
+
+    /// return the destination of the rewards not claimed in time
+    #[ink(message)]
+    fn get_expired_rewards_destination(&self) -> Option<AccountId> ;
+
+    /// send the expired rewards of the given accounts to the configured destination
+    /// Only the rewards of the eras out of the claim window expire, the accounts without expired rewards are ignored
+    #[ink(message)]
+    fn expire_rewards(&mut self, accounts: Vec<AccountId>) -> Result<(), RewardError> ;
+
+    /// return the pending rewards of the given account by era.
+    /// The rewards funded before the ledger by era was introduced are not listed.
+    /// Only the account itself or a reward viewer can call it
+    #[ink(message)]
+    fn get_pending_rewards_by_era(
+        &mut self,
+        account: AccountId,
+    ) -> Result<Vec<(u32, Balance)>, RewardError> ;
+
+    /// return the last claims of the given account, the oldest first.
+    /// Only the account itself or a reward viewer can call it
+    #[ink(message)]
+    fn get_claims(&mut self, account: AccountId) -> Result<Vec<ClaimRecord>, RewardError> ;
+
 }
 
 
@@ -86,6 +240,292 @@ pub trait Psp22RewardStorage {
 
 pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
 
+    /// Set the PSP22 token used for the rewards, None to use the native currency
+    fn inner_set_reward_token(&mut self, token: Option<AccountId>) {
+        Psp22RewardStorage::get_mut_storage(self).token.set(&token);
+    }
+
+    fn inner_get_reward_token(&self) -> Option<AccountId> {
+        Psp22RewardStorage::get_storage(self).token.get().flatten()
+    }
+
+    /// Transfer the given amount, in native currency or in PSP22 token, to the account
+    fn transfer_rewards(&mut self, to: AccountId, amount: Balance) -> Result<(), RewardError> {
+        match self.inner_get_reward_token() {
+            Some(token) => {
+                let result = build_call::<DefaultEnvironment>()
+                    .call(token)
+                    .call_v1()
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                            .push_arg(to)
+                            .push_arg(amount)
+                            .push_arg(Vec::<u8>::new()),
+                    )
+                    .returns::<Result<(), PSP22Error>>()
+                    .try_invoke();
+                match result {
+                    Ok(Ok(r)) => r.map_err(RewardError::from),
+                    _ => Err(RewardError::CrossContractCallError),
+                }
+            }
+            _ => ::ink::env::transfer::<DefaultEnvironment>(to, amount)
+                .map_err(|_| RewardError::TransferError),
+        }
+    }
+
+    /// Pull the given amount of PSP22 token from the account.
+    /// The account must approve this contract to spend the amount
+    fn pull_rewards(&mut self, token: AccountId, from: AccountId, amount: Balance) -> Result<(), RewardError> {
+        let to = ::ink::env::account_id::<DefaultEnvironment>();
+        let result = build_call::<DefaultEnvironment>()
+            .call(token)
+            .call_v1()
+            .exec_input(
+                ExecutionInput::new(Selector::new(PSP22_TRANSFER_FROM_SELECTOR))
+                    .push_arg(from)
+                    .push_arg(to)
+                    .push_arg(amount)
+                    .push_arg(Vec::<u8>::new()),
+            )
+            .returns::<Result<(), PSP22Error>>()
+            .try_invoke();
+        match result {
+            Ok(Ok(r)) => r.map_err(RewardError::from),
+            _ => Err(RewardError::CrossContractCallError),
+        }
+    }
+
+    fn inner_get_total_pending(&self) -> Balance {
+        Psp22RewardStorage::get_storage(self).total_pending.get().unwrap_or_default()
+    }
+
+    fn add_total_pending(&mut self, amount: Balance) -> Result<(), RewardError> {
+        let total_pending = self
+            .inner_get_total_pending()
+            .checked_add(amount)
+            .ok_or(RewardError::AddOverFlow)?;
+        Psp22RewardStorage::get_mut_storage(self).total_pending.set(&total_pending);
+        Ok(())
+    }
+
+    /// The rewards funded before the total was tracked are only counted if they have been
+    /// declared in the migration, so the total cannot go below zero
+    fn sub_total_pending(&mut self, amount: Balance) {
+        let total_pending = self.inner_get_total_pending().saturating_sub(amount);
+        Psp22RewardStorage::get_mut_storage(self).total_pending.set(&total_pending);
+    }
+
+    /// return the balance of the contract, in native currency or in PSP22 token
+    fn get_rewards_balance(&self) -> Result<Balance, RewardError> {
+        match self.inner_get_reward_token() {
+            Some(token) => {
+                let owner = ::ink::env::account_id::<DefaultEnvironment>();
+                let result = build_call::<DefaultEnvironment>()
+                    .call(token)
+                    .call_v1()
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(PSP22_BALANCE_OF_SELECTOR))
+                            .push_arg(owner),
+                    )
+                    .returns::<Balance>()
+                    .try_invoke();
+                match result {
+                    Ok(Ok(balance)) => Ok(balance),
+                    _ => Err(RewardError::CrossContractCallError),
+                }
+            }
+            _ => Ok(::ink::env::balance::<DefaultEnvironment>()),
+        }
+    }
+
+    fn inner_get_solvency(&self) -> Result<Solvency, RewardError> {
+        let balance = self.get_rewards_balance()?;
+        let total_pending = self.inner_get_total_pending();
+        Ok(Solvency {
+            balance,
+            total_pending,
+            surplus: balance.saturating_sub(total_pending),
+        })
+    }
+
+    fn inner_get_current_era(&self) -> u32 {
+        Psp22RewardStorage::get_storage(self).current_era.get().unwrap_or_default()
+    }
+
+    fn inner_set_claim_window_eras(&mut self, claim_window_eras: u32) {
+        Psp22RewardStorage::get_mut_storage(self).claim_window_eras.set(&claim_window_eras);
+    }
+
+    fn inner_get_claim_window_eras(&self) -> u32 {
+        Psp22RewardStorage::get_storage(self).claim_window_eras.get().unwrap_or_default()
+    }
+
+    fn inner_set_expired_rewards_destination(&mut self, destination: Option<AccountId>) {
+        Psp22RewardStorage::get_mut_storage(self).expired_rewards_destination.set(&destination);
+    }
+
+    fn inner_get_expired_rewards_destination(&self) -> Option<AccountId> {
+        Psp22RewardStorage::get_storage(self).expired_rewards_destination.get().flatten()
+    }
+
+    /// return true if the rewards funded in the given era have not been claimed in time
+    fn is_era_expired(&self, era: u32) -> bool {
+        let claim_window_eras = self.inner_get_claim_window_eras();
+        claim_window_eras != 0 && self.inner_get_current_era().saturating_sub(era) > claim_window_eras
+    }
+
+    /// Record the rewards funded for the account in the given era
+    fn add_pending_reward_by_era(
+        &mut self,
+        account: AccountId,
+        era: u32,
+        reward: Balance,
+    ) -> Result<(), RewardError> {
+        let data = Psp22RewardStorage::get_mut_storage(self);
+        let amount = match data.pending_rewards_by_era.get((era, account)) {
+            Some(existing_reward) => existing_reward.checked_add(reward).ok_or(RewardError::AddOverFlow)?,
+            _ => {
+                let mut eras = data.pending_eras.get(account).unwrap_or_default();
+                eras.push(era);
+                data.pending_eras.insert(account, &eras);
+                reward
+            }
+        };
+        data.pending_rewards_by_era.insert((era, account), &amount);
+        Ok(())
+    }
+
+    /// Remove and return the pending rewards of the account by era, the expired ones or the other ones.
+    /// Return the removed rewards by era and their sum
+    fn take_pending_rewards_by_era(
+        &mut self,
+        account: AccountId,
+        expired: bool,
+    ) -> Result<(Vec<(u32, Balance)>, Balance), RewardError> {
+        let eras = Psp22RewardStorage::get_storage(self)
+            .pending_eras
+            .get(account)
+            .unwrap_or_default();
+        let mut kept_eras = Vec::new();
+        let mut rewards = Vec::new();
+        let mut total = Balance::default();
+        for era in eras {
+            if self.is_era_expired(era) != expired {
+                kept_eras.push(era);
+                continue;
+            }
+            if let Some(amount) = Psp22RewardStorage::get_mut_storage(self)
+                .pending_rewards_by_era
+                .take((era, account))
+            {
+                total = total.checked_add(amount).ok_or(RewardError::AddOverFlow)?;
+                rewards.push((era, amount));
+            }
+        }
+        let data = Psp22RewardStorage::get_mut_storage(self);
+        if kept_eras.is_empty() {
+            data.pending_eras.remove(account);
+        } else {
+            data.pending_eras.insert(account, &kept_eras);
+        }
+        Ok((rewards, total))
+    }
+
+    /// Return the sum of the pending rewards of the account not claimed in time
+    fn get_expired_rewards(&self, account: AccountId) -> Result<Balance, RewardError> {
+        let mut total = Balance::default();
+        for (era, amount) in self.inner_get_pending_rewards_by_era(account) {
+            if self.is_era_expired(era) {
+                total = total.checked_add(amount).ok_or(RewardError::AddOverFlow)?;
+            }
+        }
+        Ok(total)
+    }
+
+    /// Remove the given amount from the pending rewards of the account
+    fn sub_pending_rewards(&mut self, account: AccountId, amount: Balance) -> Result<(), RewardError> {
+        let data = Psp22RewardStorage::get_mut_storage(self);
+        let pending_rewards = data
+            .pending_rewards
+            .get(account)
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .ok_or(RewardError::SubOverFlow)?;
+        if pending_rewards == 0 {
+            data.pending_rewards.remove(account);
+        } else {
+            data.pending_rewards.insert(account, &pending_rewards);
+        }
+        Ok(())
+    }
+
+    fn inner_get_pending_rewards_by_era(&self, account: AccountId) -> Vec<(u32, Balance)> {
+        let data = Psp22RewardStorage::get_storage(self);
+        data.pending_eras
+            .get(account)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|era| data.pending_rewards_by_era.get((era, account)).map(|amount| (era, amount)))
+            .collect()
+    }
+
+    /// Add the claim in the history of the account, the oldest claims are removed
+    fn add_claim(
+        &mut self,
+        account: AccountId,
+        amount: Balance,
+        destination: AccountId,
+        eras: Vec<(u32, Balance)>,
+    ) {
+        let data = Psp22RewardStorage::get_mut_storage(self);
+        let mut claims = data.claims.get(account).unwrap_or_default();
+        claims.push(ClaimRecord {
+            block_number: ::ink::env::block_number::<DefaultEnvironment>(),
+            amount,
+            destination,
+            eras,
+        });
+        if claims.len() > MAX_CLAIMS_HISTORY as usize {
+            claims.remove(0);
+        }
+        data.claims.insert(account, &claims);
+    }
+
+    fn inner_get_claims(&self, account: AccountId) -> Vec<ClaimRecord> {
+        Psp22RewardStorage::get_storage(self).claims.get(account).unwrap_or_default()
+    }
+
+    fn inner_expire_rewards(&mut self, accounts: Vec<AccountId>) -> Result<(), RewardError> {
+        if self.inner_get_claim_window_eras() == 0 {
+            return Err(RewardError::ClaimWindowNotSet);
+        }
+        let destination = self
+            .inner_get_expired_rewards_destination()
+            .ok_or(RewardError::ExpiredRewardsDestinationMissing)?;
+
+        let mut total_expired = Balance::default();
+        for account in accounts {
+            // only the rewards of the eras out of the claim window expire
+            let (_, amount) = self.take_pending_rewards_by_era(account, true)?;
+            if amount == 0 {
+                continue;
+            }
+            self.sub_pending_rewards(account, amount)?;
+            total_expired = total_expired.checked_add(amount).ok_or(RewardError::AddOverFlow)?;
+            // emit the event
+            ::ink::env::emit_event::<DefaultEnvironment, RewardExpired>(
+                RewardExpired{account, amount, destination}
+            );
+        }
+
+        if total_expired > 0 {
+            self.sub_total_pending(total_expired);
+            self.transfer_rewards(destination, total_expired)?;
+        }
+        Ok(())
+    }
+
     /// Add the accounts in the list of winners for a given era
     /// accounts contains the list of winners and the rewards by account
     fn inner_fund_rewards_and_add_winners(
@@ -93,6 +533,19 @@ pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
         era: u32,
         accounts: Vec<(AccountId, Balance)>,
     ) -> Result<(), RewardError> {
+        let rewards = accounts
+            .into_iter()
+            .map(|(account, reward)| (era, account, reward))
+            .collect();
+        self.inner_fund_rewards_by_era(rewards)
+    }
+
+    /// Add the winners of several eras and fund their rewards
+    /// In token mode, the rewards are pulled from the caller with PSP22 `transfer_from`
+    fn inner_fund_rewards_by_era(
+        &mut self,
+        rewards: Vec<(u32, AccountId, Balance)>,
+    ) -> Result<(), RewardError> {
 
         let caller = ::ink::env::caller::<DefaultEnvironment>();
         self.inner_check_role(REWARD_MANAGER_ROLE, caller)?;
@@ -100,8 +553,12 @@ pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
         let transferred_value = ::ink::env::transferred_value::<DefaultEnvironment>();
         let mut total_rewards = Balance::default();
 
-        // iterate on the accounts (the winners)
-        for (account, reward) in accounts {
+        // iterate on the winners
+        for (era, account, reward) in rewards {
+            if era > self.inner_get_current_era() {
+                Psp22RewardStorage::get_mut_storage(self).current_era.set(&era);
+            }
+
             total_rewards = total_rewards.checked_add(reward).ok_or(RewardError::AddOverFlow)?;
 
             // compute the new rewards for this winner
@@ -114,6 +571,7 @@ pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
             Psp22RewardStorage::get_mut_storage(self)
                 .pending_rewards
                 .insert(account, &new_reward);
+            self.add_pending_reward_by_era(account, era, reward)?;
 
             // emit the event
             ::ink::env::emit_event::<DefaultEnvironment, PendingReward>(
@@ -121,8 +579,20 @@ pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
             );
         }
 
-        if transferred_value < total_rewards {
-            return Err(RewardError::InsufficientTransferredBalance);
+        self.add_total_pending(total_rewards)?;
+
+        match self.inner_get_reward_token() {
+            Some(token) => {
+                if transferred_value > 0 {
+                    return Err(RewardError::UnexpectedTransferredValue);
+                }
+                self.pull_rewards(token, caller, total_rewards)?;
+            }
+            _ => {
+                if transferred_value < total_rewards {
+                    return Err(RewardError::InsufficientTransferredBalance);
+                }
+            }
         }
 
         Ok(())
@@ -140,21 +610,93 @@ pub trait BasePsp22Reward: Psp22RewardStorage + BaseAccessControl {
         Ok(Psp22RewardStorage::get_storage(self).pending_rewards.get(from))
     }
 
+    fn inner_set_payout_address(&mut self, payout_address: Option<AccountId>) -> Result<(), RewardError> {
+        let account = ::ink::env::caller::<DefaultEnvironment>();
+        match payout_address {
+            Some(payout_address) => {
+                Psp22RewardStorage::get_mut_storage(self).payout_addresses.insert(account, &payout_address);
+            }
+            _ => Psp22RewardStorage::get_mut_storage(self).payout_addresses.remove(account),
+        }
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, PayoutAddressUpdated>(
+            PayoutAddressUpdated{account, payout_address}
+        );
+        Ok(())
+    }
+
+    fn inner_get_payout_address(&self, account: AccountId) -> Option<AccountId> {
+        Psp22RewardStorage::get_storage(self).payout_addresses.get(account)
+    }
+
+    fn inner_set_claim_operator(&mut self, operator: Option<AccountId>) -> Result<(), RewardError> {
+        let account = ::ink::env::caller::<DefaultEnvironment>();
+        match operator {
+            Some(operator) => {
+                Psp22RewardStorage::get_mut_storage(self).claim_operators.insert(account, &operator);
+            }
+            _ => Psp22RewardStorage::get_mut_storage(self).claim_operators.remove(account),
+        }
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, ClaimOperatorUpdated>(
+            ClaimOperatorUpdated{account, operator}
+        );
+        Ok(())
+    }
+
+    fn inner_get_claim_operator(&self, account: AccountId) -> Option<AccountId> {
+        Psp22RewardStorage::get_storage(self).claim_operators.get(account)
+    }
+
+    /// Claim the rewards of the account and send them to its payout address, or to the account itself.
+    /// Anyone can call it (ie keepers)
     fn inner_claim_from(&mut self, from: AccountId) -> Result<(), RewardError> {
+        let to = self.inner_get_payout_address(from).unwrap_or(from);
+        self.inner_claim_to(from, to)
+    }
+
+    /// Claim the rewards of the account and send them to its payout address, or to the account itself.
+    /// Only the account itself or its claim operator can call it, the operator cannot choose the destination
+    fn inner_claim_for(&mut self, from: AccountId) -> Result<(), RewardError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        if caller != from && self.inner_get_claim_operator(from) != Some(caller) {
+            return Err(RewardError::NotClaimOperator);
+        }
+        let to = self.inner_get_payout_address(from).unwrap_or(from);
+        self.inner_claim_to(from, to)
+    }
+
+    fn inner_claim_to(&mut self, from: AccountId, to: AccountId) -> Result<(), RewardError> {
         // get all pending rewards for this account
         match Psp22RewardStorage::get_storage(self).pending_rewards.get(from) {
             Some(pending_rewards) => {
+                // the rewards not claimed in time are kept until they are expired
+                let expired_rewards = self.get_expired_rewards(from)?;
+                let pending_rewards = pending_rewards
+                    .checked_sub(expired_rewards)
+                    .ok_or(RewardError::SubOverFlow)?;
+                if pending_rewards == 0 {
+                    return Err(RewardError::RewardsExpired);
+                }
                 // remove the pending rewards
-                Psp22RewardStorage::get_mut_storage(self).pending_rewards.remove(from);
+                self.sub_pending_rewards(from, pending_rewards)?;
+                self.sub_total_pending(pending_rewards);
+                // keep the eras making up the claimed rewards in the history
+                let (eras, _) = self.take_pending_rewards_by_era(from, false)?;
+                self.add_claim(from, pending_rewards, to, eras);
                 // emit the event
                 ::ink::env::emit_event::<DefaultEnvironment, RewardsClaimed>(
                     RewardsClaimed{account:from, amount:pending_rewards}
                 );
+                if to != from {
+                    ::ink::env::emit_event::<DefaultEnvironment, RewardsPaidOut>(
+                        RewardsPaidOut{account:from, payout_address:to, amount:pending_rewards}
+                    );
+                }
 
 
                 // transfer the amount
-                ::ink::env::transfer::<DefaultEnvironment>(from, pending_rewards)
-                    .map_err(|_| RewardError::TransferError)?;
+                self.transfer_rewards(to, pending_rewards)?;
                 Ok(())
             }
             _ => Err(RewardError::NoReward),
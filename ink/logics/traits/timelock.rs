@@ -0,0 +1,220 @@
+use ink::env::hash::{Blake2x256, HashOutput};
+use ink::env::DefaultEnvironment;
+use ink::primitives::{AccountId, Hash};
+use ink::scale::Encode;
+use ink::storage::{Lazy, Mapping};
+use inkv5_client_lib::traits::access_control::{AccessControlError, BaseAccessControl, ADMIN_ROLE};
+
+/// Identifier of an operation: hash of the encoded operation
+pub type OperationId = <Blake2x256 as HashOutput>::Type;
+
+/// Delay used when no delay has been set, ie for the contracts deployed before the timelock
+/// (about one day with blocks of 12 seconds)
+pub const DEFAULT_MIN_DELAY: u32 = 7_200;
+
+/// Operations executed after the timelock delay.
+/// The operations specific to a contract are wrapped in `Contract`
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum Operation<T> {
+    /// upgrade the contract with the new code hash
+    UpgradeContract(Hash),
+    /// terminate the contract and send the balance to the beneficiary
+    TerminateContract(AccountId),
+    /// set the number of blocks between the schedule and the execution of an operation
+    SetMinDelay(u32),
+    /// operation specific to the contract
+    Contract(T),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum TimelockError {
+    OperationAlreadyScheduled,
+    OperationNotScheduled,
+    OperationNotReady,
+    AddOverFlow,
+    AccessControlError(AccessControlError),
+    /// the delay must be at least one block so that an operation cannot be executed in the block it is scheduled
+    InvalidMinDelay,
+    UpgradeError,
+}
+
+/// convertor from AccessControlError to TimelockError
+impl From<AccessControlError> for TimelockError {
+    fn from(error: AccessControlError) -> Self {
+        TimelockError::AccessControlError(error)
+    }
+}
+
+/// The data are not stored in the root of the contract,
+/// so the timelock can be added in a contract already deployed
+#[derive(Default, Debug)]
+#[ink::storage_item]
+pub struct TimelockData {
+    /// number of blocks between the schedule and the execution of an operation
+    min_delay: Lazy<u32>,
+    /// block from which the operation can be executed, by operation
+    operations: Mapping<OperationId, u32>,
+}
+
+/// Event emitted when an operation is scheduled
+#[ink::event]
+pub struct OperationScheduled {
+    #[ink(topic)]
+    id: OperationId,
+    ready_at: u32,
+}
+
+/// Event emitted when an operation is executed
+#[ink::event]
+pub struct OperationExecuted {
+    #[ink(topic)]
+    id: OperationId,
+}
+
+/// Event emitted when an operation is cancelled
+#[ink::event]
+pub struct OperationCancelled {
+    #[ink(topic)]
+    id: OperationId,
+}
+
+#[ink::trait_definition]
+pub trait Timelock {
+    /// Return the number of blocks between the schedule and the execution of an operation
+    #[ink(message)]
+    fn get_min_delay(&self) -> u32;
+
+    /// Return the block from which the operation can be executed, None if it is not scheduled
+    #[ink(message)]
+    fn get_operation_ready_at(&self, id: OperationId) -> Option<u32>;
+}
+
+pub trait TimelockStorage {
+    fn get_storage(&self) -> &TimelockData;
+    fn get_mut_storage(&mut self) -> &mut TimelockData;
+}
+
+pub trait BaseTimelock: TimelockStorage + BaseAccessControl {
+
+    /// Return the identifier of the operation
+    fn hash_operation<T: Encode>(operation: &T) -> OperationId {
+        let mut id = OperationId::default();
+        ::ink::env::hash_encoded::<Blake2x256, _>(operation, &mut id);
+        id
+    }
+
+    fn inner_get_min_delay(&self) -> u32 {
+        TimelockStorage::get_storage(self)
+            .min_delay
+            .get()
+            .unwrap_or(DEFAULT_MIN_DELAY)
+    }
+
+    /// Set the delay without role check, it must be done in the constructor or by a scheduled operation
+    fn inner_set_min_delay(&mut self, min_delay: u32) -> Result<(), TimelockError> {
+        if min_delay == 0 {
+            return Err(TimelockError::InvalidMinDelay);
+        }
+        TimelockStorage::get_mut_storage(self).min_delay.set(&min_delay);
+        Ok(())
+    }
+
+    fn inner_get_operation_ready_at(&self, id: OperationId) -> Option<u32> {
+        TimelockStorage::get_storage(self).operations.get(id)
+    }
+
+    /// Schedule the operation, it can be executed by anyone after the min delay
+    fn inner_schedule_operation<T: Encode>(&mut self, operation: &Operation<T>) -> Result<(), TimelockError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        if let Operation::SetMinDelay(0) = operation {
+            return Err(TimelockError::InvalidMinDelay);
+        }
+
+        let id = Self::hash_operation(operation);
+        if TimelockStorage::get_storage(self).operations.contains(id) {
+            return Err(TimelockError::OperationAlreadyScheduled);
+        }
+
+        let ready_at = ::ink::env::block_number::<DefaultEnvironment>()
+            .checked_add(self.inner_get_min_delay())
+            .ok_or(TimelockError::AddOverFlow)?;
+        TimelockStorage::get_mut_storage(self).operations.insert(id, &ready_at);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, OperationScheduled>(
+            OperationScheduled{id, ready_at}
+        );
+        Ok(())
+    }
+
+    fn inner_cancel_operation<T: Encode>(&mut self, operation: &Operation<T>) -> Result<(), TimelockError> {
+        let caller = ::ink::env::caller::<DefaultEnvironment>();
+        self.inner_check_role(ADMIN_ROLE, caller)?;
+
+        let id = Self::hash_operation(operation);
+        if !TimelockStorage::get_storage(self).operations.contains(id) {
+            return Err(TimelockError::OperationNotScheduled);
+        }
+        TimelockStorage::get_mut_storage(self).operations.remove(id);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, OperationCancelled>(
+            OperationCancelled{id}
+        );
+        Ok(())
+    }
+
+    /// Check the operation is ready, remove it from the scheduled operations
+    /// and execute the operations common to all contracts.
+    /// Return the operation specific to the contract, the caller must then execute it.
+    fn inner_execute_operation<T: Encode>(&mut self, operation: Operation<T>) -> Result<Option<T>, TimelockError> {
+        let id = Self::hash_operation(&operation);
+        let ready_at = TimelockStorage::get_storage(self)
+            .operations
+            .get(id)
+            .ok_or(TimelockError::OperationNotScheduled)?;
+
+        if ::ink::env::block_number::<DefaultEnvironment>() < ready_at {
+            return Err(TimelockError::OperationNotReady);
+        }
+        TimelockStorage::get_mut_storage(self).operations.remove(id);
+
+        // emit the event
+        ::ink::env::emit_event::<DefaultEnvironment, OperationExecuted>(
+            OperationExecuted{id}
+        );
+
+        match operation {
+            Operation::UpgradeContract(new_code_hash) => {
+                ::ink::env::set_code_hash::<DefaultEnvironment>(&new_code_hash)
+                    .map_err(|_| TimelockError::UpgradeError)?;
+            }
+            Operation::TerminateContract(beneficiary) => {
+                ::ink::env::terminate_contract::<DefaultEnvironment>(beneficiary);
+            }
+            Operation::SetMinDelay(min_delay) => {
+                self.inner_set_min_delay(min_delay)?;
+            }
+            Operation::Contract(operation) => return Ok(Some(operation)),
+        }
+        Ok(None)
+    }
+
+    /// Used by the messages replaced by the timelock:
+    /// the first call schedules the operation, the call after the min delay executes it
+    fn inner_schedule_or_execute_operation<T: Encode>(
+        &mut self,
+        operation: Operation<T>,
+    ) -> Result<Option<T>, TimelockError> {
+        let id = Self::hash_operation(&operation);
+        if self.inner_get_operation_ready_at(id).is_none() {
+            self.inner_schedule_operation(&operation)?;
+            return Ok(None);
+        }
+        self.inner_execute_operation(operation)
+    }
+}
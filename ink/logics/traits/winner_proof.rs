@@ -0,0 +1,319 @@
+use crate::traits::error::RaffleError;
+use ink::env::hash::{Blake2x256, CryptoHash, HashOutput};
+use ink::prelude::vec::Vec;
+use ink::primitives::AccountId;
+use ink::scale::Encode;
+
+pub type HashValue = <Blake2x256 as HashOutput>::Type;
+
+/// Prefix of the hash of a leaf, so that a node cannot be presented as a leaf
+const LEAF_PREFIX: u8 = 0x00;
+/// Prefix of the hash of a node
+const NODE_PREFIX: u8 = 0x01;
+
+/// Leaf of the participants snapshot: the account and its cumulative range of tickets [start, end)
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct TicketRange {
+    pub account: AccountId,
+    pub start: u128,
+    pub end: u128,
+}
+
+/// Proof that the ticket drawn belongs to the range of the participant
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct TicketProof {
+    pub range: TicketRange,
+    /// hashes of the sibling nodes, from the leaf to the root
+    pub proof: Vec<HashValue>,
+}
+
+/// Commitment on the participants snapshot and the random value used to draw the winners
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub struct WinnersProof {
+    /// root of the merkle tree built with the ticket ranges of the participants
+    pub snapshot_root: HashValue,
+    pub total_tickets: u128,
+    /// random value used to draw the tickets, it must match the commitment sent in advance
+    pub random: HashValue,
+    /// one proof by draw, in the order of the draws.
+    /// A draw selecting a participant already drawn or excluded is ignored.
+    pub draws: Vec<TicketProof>,
+}
+
+fn hash(input: &[u8]) -> HashValue {
+    let mut output = HashValue::default();
+    Blake2x256::hash(input, &mut output);
+    output
+}
+
+fn hash_encoded<T: Encode>(input: &T) -> HashValue {
+    hash(&input.encode())
+}
+
+/// Hash of a leaf of the snapshot
+pub fn leaf_hash(range: &TicketRange) -> HashValue {
+    let mut input = Vec::with_capacity(65);
+    input.push(LEAF_PREFIX);
+    range.encode_to(&mut input);
+    hash(&input)
+}
+
+/// Hash of a node, the children are sorted so that the proof doesn't need the position
+pub fn node_hash(left: &HashValue, right: &HashValue) -> HashValue {
+    let mut input = Vec::with_capacity(65);
+    input.push(NODE_PREFIX);
+    if left <= right {
+        input.extend_from_slice(left);
+        input.extend_from_slice(right);
+    } else {
+        input.extend_from_slice(right);
+        input.extend_from_slice(left);
+    }
+    hash(&input)
+}
+
+/// Commitment on the random value, sent before the participants of the era are known
+pub fn random_commitment(random: &HashValue) -> HashValue {
+    hash(random)
+}
+
+/// Ticket drawn for the given era and draw: blake2(random, era, draw) modulo total_tickets
+pub fn draw_ticket(random: &HashValue, era: u32, draw: u32, total_tickets: u128) -> Result<u128, RaffleError> {
+    let seed = hash_encoded(&(random, era, draw));
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&seed[..16]);
+    u128::from_le_bytes(bytes)
+        .checked_rem(total_tickets)
+        .ok_or(RaffleError::DivByZero)
+}
+
+/// Return true if the leaf belongs to the merkle tree with the given root
+pub fn verify_merkle_proof(root: &HashValue, leaf: HashValue, proof: &[HashValue]) -> bool {
+    let computed_root = proof
+        .iter()
+        .fold(leaf, |node, sibling| node_hash(&node, sibling));
+    computed_root == *root
+}
+
+/// Verify the winners are the participants drawn with the random value.
+/// Each ticket drawn must be in the range of a participant of the snapshot
+/// and the ranges of the different participants must not overlap.
+/// The snapshot contains all the participants of the era, so that it can be committed
+/// before the winners of the previous era are known: the draws selecting an excluded participant are ignored.
+pub fn verify_winners<F>(
+    era: u32,
+    winners: &[AccountId],
+    winners_proof: &WinnersProof,
+    is_excluded: F,
+) -> Result<(), RaffleError>
+where
+    F: Fn(&AccountId) -> bool,
+{
+    if winners_proof.total_tickets == 0 {
+        return Err(RaffleError::InvalidWinnersProof);
+    }
+
+    let mut drawn_winners: Vec<AccountId> = Vec::with_capacity(winners.len());
+    let mut drawn_ranges: Vec<&TicketRange> = Vec::with_capacity(winners_proof.draws.len());
+
+    for (i, draw) in winners_proof.draws.iter().enumerate() {
+        // the range must be included in the tickets of the snapshot
+        if draw.range.start >= draw.range.end || draw.range.end > winners_proof.total_tickets {
+            return Err(RaffleError::InvalidWinnersProof);
+        }
+        // the range of a participant is unique and doesn't overlap the range of another participant
+        for range in &drawn_ranges {
+            let same_participant = range.account == draw.range.account;
+            let same_range = range.start == draw.range.start && range.end == draw.range.end;
+            let overlap = range.start < draw.range.end && draw.range.start < range.end;
+            if (same_participant && !same_range) || (!same_participant && overlap) {
+                return Err(RaffleError::InvalidWinnersProof);
+            }
+        }
+        drawn_ranges.push(&draw.range);
+
+        let ticket = draw_ticket(
+            &winners_proof.random,
+            era,
+            u32::try_from(i)?,
+            winners_proof.total_tickets,
+        )?;
+        // the ticket must be in the range of the participant
+        if ticket < draw.range.start || ticket >= draw.range.end {
+            return Err(RaffleError::InvalidWinnersProof);
+        }
+        // the participant must be in the snapshot
+        if !verify_merkle_proof(
+            &winners_proof.snapshot_root,
+            leaf_hash(&draw.range),
+            &draw.proof,
+        ) {
+            return Err(RaffleError::InvalidWinnersProof);
+        }
+        // the excluded participants cannot win
+        if is_excluded(&draw.range.account) {
+            continue;
+        }
+        if !drawn_winners.contains(&draw.range.account) {
+            drawn_winners.push(draw.range.account);
+        }
+    }
+
+    if drawn_winners.as_slice() != winners {
+        return Err(RaffleError::WinnersMismatch);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOTAL_TICKETS: u128 = 60;
+
+    fn ranges() -> Vec<TicketRange> {
+        vec![
+            TicketRange { account: AccountId::from([1u8; 32]), start: 0, end: 10 },
+            TicketRange { account: AccountId::from([2u8; 32]), start: 10, end: 30 },
+            TicketRange { account: AccountId::from([3u8; 32]), start: 30, end: 60 },
+        ]
+    }
+
+    /// root of the tree: node(node(leaf0, leaf1), leaf2)
+    fn root() -> HashValue {
+        let leaves: Vec<HashValue> = ranges().iter().map(leaf_hash).collect();
+        node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2])
+    }
+
+    fn ticket_proof(index: usize) -> TicketProof {
+        let leaves: Vec<HashValue> = ranges().iter().map(leaf_hash).collect();
+        let proof = match index {
+            0 => vec![leaves[1], leaves[2]],
+            1 => vec![leaves[0], leaves[2]],
+            _ => vec![node_hash(&leaves[0], &leaves[1])],
+        };
+        TicketProof { range: ranges()[index].clone(), proof }
+    }
+
+    /// proof of the participant owning the ticket drawn
+    fn draw_proof(random: &HashValue, era: u32, draw: u32) -> TicketProof {
+        let ticket = draw_ticket(random, era, draw, TOTAL_TICKETS).unwrap();
+        let index = ranges()
+            .iter()
+            .position(|range| range.start <= ticket && ticket < range.end)
+            .unwrap();
+        ticket_proof(index)
+    }
+
+    fn winners_proof(draws: Vec<TicketProof>) -> WinnersProof {
+        WinnersProof {
+            snapshot_root: root(),
+            total_tickets: TOTAL_TICKETS,
+            random: [7u8; 32],
+            draws,
+        }
+    }
+
+    #[test]
+    fn test_valid_proof() {
+        let draw = draw_proof(&[7u8; 32], 13, 0);
+        let winner = draw.range.account;
+        let proof = winners_proof(vec![draw]);
+
+        assert_eq!(Ok(()), verify_winners(13, &[winner], &proof, |_| false));
+        assert_eq!(
+            Err(RaffleError::WinnersMismatch),
+            verify_winners(13, &[AccountId::from([9u8; 32])], &proof, |_| false)
+        );
+        // the draw of an excluded participant is ignored
+        assert_eq!(
+            Err(RaffleError::WinnersMismatch),
+            verify_winners(13, &[winner], &proof, |account| *account == winner)
+        );
+        assert_eq!(Ok(()), verify_winners(13, &[], &proof, |account| *account == winner));
+    }
+
+    #[test]
+    fn test_ticket_out_of_range() {
+        let ticket = draw_ticket(&[7u8; 32], 13, 0, TOTAL_TICKETS).unwrap();
+        // the proof of another participant
+        let index = ranges()
+            .iter()
+            .position(|range| ticket < range.start || ticket >= range.end)
+            .unwrap();
+        let draw = ticket_proof(index);
+        let winner = draw.range.account;
+        let proof = winners_proof(vec![draw]);
+
+        assert_eq!(
+            Err(RaffleError::InvalidWinnersProof),
+            verify_winners(13, &[winner], &proof, |_| false)
+        );
+    }
+
+    #[test]
+    fn test_range_not_in_snapshot() {
+        let mut draw = draw_proof(&[7u8; 32], 13, 0);
+        // the participant claims all the tickets
+        draw.range.start = 0;
+        draw.range.end = TOTAL_TICKETS;
+        let winner = draw.range.account;
+        let proof = winners_proof(vec![draw]);
+
+        assert_eq!(
+            Err(RaffleError::InvalidWinnersProof),
+            verify_winners(13, &[winner], &proof, |_| false)
+        );
+    }
+
+    #[test]
+    fn test_range_above_total_tickets() {
+        let draw = draw_proof(&[7u8; 32], 13, 0);
+        let winner = draw.range.account;
+        let mut proof = winners_proof(vec![draw.clone()]);
+        proof.total_tickets = draw.range.end - 1;
+
+        assert_eq!(
+            Err(RaffleError::InvalidWinnersProof),
+            verify_winners(13, &[winner], &proof, |_| false)
+        );
+    }
+
+    #[test]
+    fn test_overlapping_ranges() {
+        let draw = draw_proof(&[7u8; 32], 13, 0);
+        let winner = draw.range.account;
+        // another participant with a range overlapping the first one
+        let overlapping = TicketProof {
+            range: TicketRange {
+                account: AccountId::from([9u8; 32]),
+                start: draw.range.start,
+                end: draw.range.end,
+            },
+            proof: draw.proof.clone(),
+        };
+        let proof = winners_proof(vec![draw, overlapping]);
+
+        assert_eq!(
+            Err(RaffleError::InvalidWinnersProof),
+            verify_winners(13, &[winner], &proof, |_| false)
+        );
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let range = &ranges()[0];
+        // a leaf is not hashed as the encoded range
+        assert_ne!(hash_encoded(range), leaf_hash(range));
+        // a node cannot be presented as a leaf
+        let leaves: Vec<HashValue> = ranges().iter().map(leaf_hash).collect();
+        let mut input = Vec::new();
+        input.extend_from_slice(&leaves[0].min(leaves[1]));
+        input.extend_from_slice(&leaves[0].max(leaves[1]));
+        assert_ne!(hash(&input), node_hash(&leaves[0], &leaves[1]));
+    }
+}
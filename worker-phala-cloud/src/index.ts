@@ -14,18 +14,24 @@ const shibuyaClientAddress = process.env.SHIBUYA_CLIENT_CONTRACT_ADDRESS;
 const shibuyaDappStakingContractAddress = process.env.SHIBUYA_DAPP_STAKING_CONTRACT_ADDRESS;
 const shibuyaWorkerPk = process.env.SHIBUYA_WORKER_PK;
 const shibuyaIndexerUrl = process.env.SHIBUYA_INDEXER_URL;
+const shibuyaChainId = process.env.SHIBUYA_CHAIN_ID;
+const shibuyaPoolId = process.env.SHIBUYA_POOL_ID;
 
 const shidenClientRpc = process.env.SHIDEN_RPC;
 const shidenClientAddress = process.env.SHIDEN_CLIENT_CONTRACT_ADDRESS;
 const shidenDappStakingContractAddress = process.env.SHIDEN_DAPP_STAKING_CONTRACT_ADDRESS;
 const shidenWorkerPk = process.env.SHIDEN_WORKER_PK;
 const shidenIndexerUrl = process.env.SHIDEN_INDEXER_URL;
+const shidenChainId = process.env.SHIDEN_CHAIN_ID;
+const shidenPoolId = process.env.SHIDEN_POOL_ID;
 
 const astarClientRpc = process.env.ASTAR_RPC;
 const astarClientAddress = process.env.ASTAR_CLIENT_CONTRACT_ADDRESS;
 const astarDappStakingContractAddress = process.env.ASTAR_DAPP_STAKING_CONTRACT_ADDRESS;
 const astarWorkerPk = process.env.ASTAR_WORKER_PK;
 const astarIndexerUrl = process.env.ASTAR_INDEXER_URL;
+const astarChainId = process.env.ASTAR_CHAIN_ID;
+const astarPoolId = process.env.ASTAR_POOL_ID;
 
 const port = process.env.PORT || 3010;
 console.log(`Listening on port ${port}`);
@@ -67,19 +73,25 @@ async function getSubstrateKeyringPair(client: TappdClient, chain: Chain) : Prom
 
 async function getRaffleContractConfig(client: TappdClient, chain: Chain) : Promise<ContractConfig> {
 
-  let rpc, address, senderKey;
+  let rpc, address, senderKey, chainId, poolId;
   if (chain == Chain.Astar) {
     rpc = astarClientRpc;
     address = astarClientAddress;
     senderKey = astarWorkerPk;
+    chainId = astarChainId;
+    poolId = astarPoolId;
   } else if (chain == Chain.Shiden) {
     rpc = shidenClientRpc;
     address = shidenClientAddress;
     senderKey = shidenWorkerPk;
+    chainId = shidenChainId;
+    poolId = shidenPoolId;
   } else {
     rpc = shibuyaClientRpc;
     address = shibuyaClientAddress;
     senderKey = shibuyaWorkerPk;
+    chainId = shibuyaChainId;
+    poolId = shibuyaPoolId;
   }
 
   if (!rpc){
@@ -95,6 +107,8 @@ async function getRaffleContractConfig(client: TappdClient, chain: Chain) : Prom
     rpc,
     attestorKey : toHex(attestorKey),
     senderKey,
+    chainId,
+    poolId: poolId ? Number(poolId) : undefined,
   };
 }
 
@@ -1,49 +1,68 @@
-import {type AccountId, type ContractConfig, type Era, type Participant} from "./types.ts";
-import {type HexString, Option} from "@guigou/sc-rollup-core";
+import {type AccountId, type ContractConfig, type Era} from "./types.ts";
+import {Option} from "@guigou/sc-rollup-core";
 import {hexAddPrefix, hexToU8a} from "@polkadot/util";
 import {decodeAddress, encodeAddress} from "@polkadot/keyring";
 import {InkClient} from "@guigou/sc-rollup-ink-v5";
 import {
     accountIdsCodec,
-    eraCodec,
+    DEFAULT_POOL,
+    type DomainSeparator,
+    encodeResponseEnvelope,
+    LAST_COMMITTED_ERA,
     LAST_WINNER,
     NB_WINNERS,
     NEXT_ERA,
+    type PoolId,
+    poolKey,
+    RANDOM_COMMITMENT_ERA_OFFSET,
+    type RaffleAudit,
+    type RaffleResponse,
     type RaffleResponseMessage,
-    raffleResponseMessageCodec
+    type RaffleResponseMessageV2,
+    type SkipReason,
+    SNAPSHOT_COMMITMENT_ERA_OFFSET,
 } from "./wasm_codec.ts";
 import {Bytes} from "scale-ts";
 import {Indexer} from "./indexer.ts";
-import {Vrf} from "@guigou/util-crypto";
+import {buildSnapshot, deriveRandom, drawWinners, randomCommitment, type Ticket} from "./winner_proof.ts";
 
 const MAX_ERA = 999999999;
+// max number of eras sent in a single response, to stay within the gas limit of a transaction
+const MAX_ERAS_BY_RESPONSE = 20;
 
 export class RaffleConsumerContract {
     private readonly indexer;
-    private readonly client: InkClient<Uint8Array, RaffleResponseMessage>;
-    private readonly vrf : Vrf;
+    private readonly client: InkClient<Uint8Array, Uint8Array>;
+    private readonly domain: DomainSeparator | undefined;
+    private readonly poolId: PoolId;
+    // secret seed used to derive the random values committed in advance
+    private readonly randomSeed : Uint8Array;
 
-    constructor(config: ContractConfig | null, indexer: Indexer, vrf: Vrf) {
+    constructor(config: ContractConfig | null, indexer: Indexer, randomSeed: Uint8Array) {
 
 
         if (!config) throw new Error('WasmContractNotConfigured');
 
-        this.client = new InkClient<Uint8Array, RaffleResponseMessage>(
+        this.client = new InkClient<Uint8Array, Uint8Array>(
             config.rpc,
             config.address,
             hexAddPrefix(config.attestorKey),
             config.senderKey ? hexAddPrefix(config.senderKey) : undefined,
             Bytes(),
-            raffleResponseMessageCodec
+            Bytes()
         );
+        this.domain = config.chainId
+            ? {chainId: hexToU8a(config.chainId), contract: decodeAddress(config.address)}
+            : undefined;
+        this.poolId = config.poolId ?? DEFAULT_POOL;
         this.indexer = indexer;
-        this.vrf = vrf;
+        this.randomSeed = randomSeed;
 
     }
 
     async getNextEra(): Promise<Option<Era>> {
         try {
-            return await this.client.getNumber(NEXT_ERA, 'u32');
+            return await this.client.getNumber(poolKey(NEXT_ERA, this.poolId), 'u32');
         } catch (err) {
             console.error('Next era unknown in kv store');
             throw new Error('NextEraUnknown');
@@ -52,7 +71,7 @@ export class RaffleConsumerContract {
 
     async getNbWinners(): Promise<Option<number>> {
         try {
-            return await this.client.getNumber(NB_WINNERS, 'u16');
+            return await this.client.getNumber(poolKey(NB_WINNERS, this.poolId), 'u16');
         } catch (err) {
             console.error('Nb winners unknown in kv store');
             throw new Error('NbWinnersUnknown');
@@ -61,7 +80,7 @@ export class RaffleConsumerContract {
 
     async getLastWinners(): Promise<Option<AccountId[]>> {
         try {
-            const bytes = await this.client.getBytes(LAST_WINNER);
+            const bytes = await this.client.getBytes(poolKey(LAST_WINNER, this.poolId));
             return bytes.map(accountIdsCodec.dec);
         } catch (err) {
             console.error('Last winners unknown in kv store');
@@ -69,6 +88,17 @@ export class RaffleConsumerContract {
         }
     }
 
+    async getLastCommittedEra(): Promise<Era> {
+        try {
+            const oEra = await this.client.getNumber(poolKey(LAST_COMMITTED_ERA, this.poolId), 'u32');
+            // nothing is committed before the first response sent by this version of the worker
+            return oEra.orElse(0);
+        } catch (err) {
+            console.error('Last committed era unknown in kv store');
+            throw new Error('LastCommittedEraUnknown');
+        }
+    }
+
 
     async runRaffle(targetEra: Era) {
 
@@ -87,103 +117,175 @@ export class RaffleConsumerContract {
             throw new Error('nbWinners is not set');
         }
         while (era <= targetEra){
-            console.log("Run raffle for era %s", era);
-            const tx = await this.runRaffleForEra(era, nbWinners);
+            // the excluded addresses and the commitments are read again after each transaction
+            const oExcluded = await this.getLastWinners();
+            const excluded = oExcluded.valueOf();
+            if (!excluded){
+                throw new Error('Last winners are not set');
+            }
+            const lastCommittedEra = await this.getLastCommittedEra();
+
+            // the results of the pending eras are sent in a single envelope.
+            // A raffle with winners must be the first era of the envelope: its winners are excluded from the next raffles
+            // and its commitments must have been sent in a previous transaction
+            const lastEra = Math.min(targetEra, era + MAX_ERAS_BY_RESPONSE - 1);
+            const responses: RaffleResponseMessageV2[] = [];
+            while (era <= lastEra) {
+                console.log("Run raffle for era %s", era);
+                const response = await this.runRaffleForEra(era, nbWinners, excluded, era <= lastCommittedEra);
+                if (responses.length > 0 && response.response.winners.length > 0) {
+                    break;
+                }
+                responses.push(response);
+                era++;
+            }
+            this.addAction(responses);
+            const tx = await this.client.commit();
             console.log("Submit transaction : " + tx);
             era = (await this.getNextEra()).orElse(MAX_ERA);
         }
     }
 
-    private async runRaffleForEra(era: Era, nbWinners: number): Promise<Option<HexString>> {
+    private async runRaffleForEra(
+        era: Era,
+        nbWinners: number,
+        excluded: AccountId[],
+        committed: boolean,
+    ): Promise<RaffleResponseMessageV2> {
 
         const eraInfo = await this.indexer.getEraInfo(era);
+        // commit the snapshot of the next era, its participants are known at the end of this era
+        const nextSnapshot = await this.getSnapshotCommitment(era + SNAPSHOT_COMMITMENT_ERA_OFFSET);
 
         if (eraInfo.subPeriod.toUpperCase() == 'VOTING') {
             console.log(`Voting subPeriod for era: ${era} => skip the raffle`);
+            return this.buildSkipResponse(era, 'VotingSubPeriod', BigInt(0), nextSnapshot);
+        }
 
-            const action = {
-                era,
-                skipped: true,
-                rewards: BigInt(0),
-                winners: [],
-            };
-            this.client.addAction(action);
-
-        } else {
-            console.log(`BuildAndEarn subPeriod for era: ${era} => run raffle`);
-
-            const rewards = await this.indexer.getRewards(era);
-            console.log(`Total rewards for this era: ${rewards}`);
+        console.log(`BuildAndEarn subPeriod for era: ${era} => run raffle`);
 
-            let participants = await this.indexer.getParticipants(eraInfo.period, era);
-            console.log(`Nb of participants: ${participants.length}`);
+        const rewards = await this.getRewards(era);
+        console.log(`Total rewards for this era: ${rewards}`);
+        if (rewards == BigInt(0)) {
+            console.log(`No reward for era: ${era} => skip the raffle`);
+            return this.buildSkipResponse(era, 'NoReward', rewards, nextSnapshot);
+        }
 
-            const oExcluded = await this.getLastWinners();
-            const excluded = oExcluded.valueOf();
-            if (!excluded){
-                throw new Error('Last winners are not set');
-            }
-            const participantExcluded = convertAddressesToString(excluded);
-            console.log(`Exclude these participants: ${participantExcluded}`);
-            participants = excludeParticipants(participants, participantExcluded);
-
-            let winners: string[] = [];
-            for (let i = 0; i < nbWinners; i++) {
-                const winner = this.selectWinner(era, participants);
-                winners.push(winner);
-                participants = excludeParticipants(participants, [winner]);
-            }
+        const tickets = await this.getTickets(eraInfo.period, era);
+        console.log(`Nb of participants: ${tickets.length}`);
+        console.log(`Exclude these participants: ${convertAddressesToString(excluded)}`);
+        if (!tickets.some((ticket) => ticket.nbTickets > 0)) {
+            console.log(`No participant for era: ${era} => skip the raffle`);
+            return this.buildSkipResponse(era, 'NoParticipant', rewards, nextSnapshot);
+        }
 
-            console.log(`Winners: ${winners}`);
+        // draw the winners with the random value committed in advance, the excluded participants are ignored
+        const random = deriveRandom(this.randomSeed, this.poolId, era);
+        const {winners, winnersProof} = drawWinners(random, era, tickets, nbWinners, excluded);
+        console.log(`Total tickets : ${winnersProof.totalTickets}`);
+        console.log(`Winners: ${convertAddressesToString(winners)}`);
+        if (winners.length == 0) {
+            // all the participants are excluded, the rewards are kept in the jackpot (if enabled)
+            console.log(`No participant allowed for era: ${era} => skip the raffle`);
+            return this.buildSkipResponse(era, 'NoParticipant', rewards, nextSnapshot);
+        }
 
-            const action = {
-                era,
-                skipped: false,
-                rewards,
-                winners : convertAddressesFromString(winners),
-            };
+        const action: RaffleResponseMessage = {
+            era,
+            skipReason: undefined,
+            rewards,
+            winners,
+            // the proof is only accepted when the random value and the snapshot have been committed
+            winnersProof: committed ? winnersProof : undefined,
+        };
+        const audit: RaffleAudit = {
+            nbParticipants: tickets.length,
+            totalTickets: winnersProof.totalTickets,
+            snapshotHash: winnersProof.snapshotRoot,
+            randomness: random,
+        };
+        return this.buildResponse(action, audit, nextSnapshot);
+    }
 
-            this.client.addAction(action);
+    // rewards of the era, zero if the dApp has not received rewards for this era
+    private async getRewards(era: Era): Promise<bigint> {
+        try {
+            return await this.indexer.getRewards(era);
+        } catch (err) {
+            if (err instanceof Error && err.message == 'NoReward') {
+                return BigInt(0);
+            }
+            throw err;
         }
-        return this.client.commit();
     }
 
+    // tickets of the participants of the era
+    private async getTickets(period: string, era: Era): Promise<Ticket[]> {
+        const participants = await this.indexer.getParticipants(period, era);
+        return participants.map((p) => ({
+            account: convertAddressFromString(p.address),
+            nbTickets: BigInt(p.nbTickets),
+        }));
+    }
 
-    private selectWinner(era: Era, participants: Participant[]): string {
-
-        let totalTickets = 0;
-
-        for (let i = 0; i < participants.length; i++) {
-            totalTickets += participants[i].nbTickets;
+    // root of the snapshot of the participants of the era, undefined if there is no raffle for this era
+    private async getSnapshotCommitment(era: Era): Promise<Uint8Array | undefined> {
+        const eraInfo = await this.indexer.getEraInfo(era);
+        if (eraInfo.subPeriod.toUpperCase() == 'VOTING') {
+            return undefined;
         }
-
-        if (totalTickets == 0) {
-            throw new Error("NoMoreParticipant");
+        const tickets = await this.getTickets(eraInfo.period, era);
+        if (!tickets.some((ticket) => ticket.nbTickets > 0)) {
+            return undefined;
         }
+        return buildSnapshot(tickets).root;
+    }
 
-        console.log(`Total tickets : ${totalTickets}`);
-        // build the salt used by the vrf
-        const vrfSalt = eraCodec.enc(era)
-        // draw the number
-        const winningTicket = this.vrf.getRandomNumber(vrfSalt, 0, totalTickets);
-
-        console.log(`Winning ticket : ${winningTicket}`);
-        let currentTicket = 0;
-        for (let i = 0; i < participants.length; i++) {
-            currentTicket += participants[i].nbTickets;
-            if (currentTicket >= winningTicket) {
-                return participants[i].address;
-            }
-        }
-        throw new Error("NoWinnerFound");
+    private buildSkipResponse(
+        era: Era,
+        reason: SkipReason['tag'],
+        rewards: bigint,
+        nextSnapshot: Uint8Array | undefined,
+    ): RaffleResponseMessageV2 {
+        const action: RaffleResponseMessage = {
+            era,
+            skipReason: {tag: reason, value: undefined},
+            rewards,
+            winners: [],
+            winnersProof: undefined,
+        };
+        return this.buildResponse(action, undefined, nextSnapshot);
+    }
+
+    private buildResponse(
+        action: RaffleResponseMessage,
+        audit: RaffleAudit | undefined,
+        nextSnapshot: Uint8Array | undefined,
+    ): RaffleResponseMessageV2 {
+        // commit the random value of a next era, before its participants are known
+        const nextRandom = deriveRandom(this.randomSeed, this.poolId, action.era + RANDOM_COMMITMENT_ERA_OFFSET);
+        return {
+            response: action,
+            audit,
+            nextRandomCommitment: randomCommitment(nextRandom),
+            nextSnapshotCommitment: nextSnapshot,
+        };
     }
-}
 
-function excludeParticipants(participants: Participant[], excluded: string[]): Participant[] {
-    if (excluded == undefined) {
-        return participants;
+    private addAction(responses: RaffleResponseMessageV2[]) {
+        const response: RaffleResponse = {
+            tag: 'V2',
+            value: {
+                poolId: this.poolId,
+                responses,
+            },
+        };
+        // bind the response to the deployment when the chain id is known
+        const encoded = this.domain
+            ? encodeResponseEnvelope({tag: 'Bound', value: {domain: this.domain, response}})
+            : encodeResponseEnvelope({tag: 'Unbound', value: response});
+        this.client.addAction(encoded);
     }
-    return participants.filter((p, i) => !excluded.includes(p.address));
 }
 
 function convertAddressesToString(addresses: AccountId[]) : string[] {
@@ -194,10 +296,6 @@ function convertAddressToString(address: AccountId) : string {
     return encodeAddress(address, 5);
 }
 
-function convertAddressesFromString(addresses: string[]) : AccountId[] {
-    return addresses.map(convertAddressFromString);
-}
-
 function convertAddressFromString(address: string) : AccountId {
     return decodeAddress(address, false, 5);
 }
\ No newline at end of file
@@ -9,6 +9,10 @@ export interface ContractConfig {
     address: string;
     attestorKey: string;
     senderKey?: string;
+    // identifier of the chain (genesis hash) set in the contract, used in the domain separator
+    chainId?: string;
+    // pool of the dApp in the contract, the default pool if not set
+    poolId?: number;
 }
 
 export interface DappStakingProxyConfig {
@@ -1,11 +1,29 @@
 import {type AccountId, type Balance, type Era} from './types';
-import {bool, Bytes, type Codec, Struct, u128, u32, Vector} from "scale-ts";
+import {u8aToHex} from "@polkadot/util";
+import {_void, bool, Bytes, type Codec, type CodecType, Enum, Option, Struct, u128, u32, Vector} from "scale-ts";
 
 
 // Constants
 export const NEXT_ERA = '0xe6608356'; // assuming ink::selector_id!("NEXT_ERA")
 export const NB_WINNERS = '0x021f707b'; // assuming ink::selector_id!("NB_WINNERS")
 export const LAST_WINNER = '0x3d96da39'; // assuming ink::selector_id!("LAST_WINNER")
+export const JACKPOT = '0xb7bb85e5'; // ink::selector_id!("JACKPOT"), checked in wasm_codec.test.ts
+export const PROTOCOL_FEE = '0xef63a0a2'; // ink::selector_id!("PROTOCOL_FEE"), checked in wasm_codec.test.ts
+export const LAST_COMMITTED_ERA = '0xf244ff67'; // ink::selector_id!("LAST_COMMITTED_ERA"), checked in wasm_codec.test.ts
+
+export type PoolId = number;
+
+// pool existing before the pools were introduced
+export const DEFAULT_POOL: PoolId = 0;
+
+// key of the data stored by pool in the kv store: the pool id is appended to the key,
+// except for the default pool which uses the key as it is
+export function poolKey(key: string, poolId: PoolId): string {
+    if (poolId == DEFAULT_POOL) {
+        return key;
+    }
+    return key + u8aToHex(u32.enc(poolId), -1, false);
+}
 
 
 export const accountIdCodec : Codec<AccountId> = Bytes(32);
@@ -13,28 +31,202 @@ export const accountIdsCodec : Codec<AccountId[]> = Vector(accountIdCodec);
 
 export const eraCodec : Codec<Era> = u32;
 
+export const hashCodec : Codec<Uint8Array> = Bytes(32);
+
+export type TicketRange = {
+    account: AccountId,
+    start: bigint,
+    end: bigint,
+}
+
+export const ticketRangeCodec : Codec<TicketRange> = Struct({
+    account: accountIdCodec,
+    start: u128,
+    end: u128,
+});
+
+export type TicketProof = {
+    range: TicketRange,
+    proof: Uint8Array[],
+}
+
+export type WinnersProof = {
+    snapshotRoot: Uint8Array,
+    totalTickets: bigint,
+    random: Uint8Array,
+    draws: TicketProof[],
+}
+
+export const winnersProofCodec : Codec<WinnersProof> = Struct({
+    snapshotRoot: hashCodec,
+    totalTickets: u128,
+    random: hashCodec,
+    draws: Vector(Struct({
+        range: ticketRangeCodec,
+        proof: Vector(hashCodec),
+    })),
+});
+
+export const skipReasonCodec = Enum({
+    VotingSubPeriod: _void,
+    NoParticipant: _void,
+    NoReward: _void,
+    OperatorDecision: _void,
+});
+
+export type SkipReason = CodecType<typeof skipReasonCodec>;
+
 /*
     #[ink::scale_derive(Encode, Decode)]
     pub struct RaffleResponseMessage {
         pub era: u32,
-        pub skipped: bool,
+        pub skip_reason: Option<SkipReason>,
         pub rewards: Balance,
         pub winners: Vec<AccountId>,
+        pub winners_proof: Option<WinnersProof>,
     }
  */
 
 export type RaffleResponseMessage = {
     era: Era,
-    skipped: boolean,
+    skipReason: SkipReason | undefined,
     rewards: Balance,
     winners: AccountId[],
+    winnersProof: WinnersProof | undefined,
 }
 
 export const raffleResponseMessageCodec : Codec<RaffleResponseMessage> = Struct({
+    era: eraCodec,
+    skipReason: Option(skipReasonCodec),
+    rewards: u128,
+    winners: Vector(accountIdCodec),
+    winnersProof: Option(winnersProofCodec),
+});
+
+/*
+    // result sent by the first version of the worker
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct RaffleResponseMessageV1 {
+        pub era: u32,
+        pub skipped: bool,
+        pub rewards: Balance,
+        pub winners: Vec<AccountId>,
+    }
+ */
+
+export type RaffleResponseMessageV1 = {
+    era: Era,
+    skipped: boolean,
+    rewards: Balance,
+    winners: AccountId[],
+}
+
+export const raffleResponseMessageV1Codec : Codec<RaffleResponseMessageV1> = Struct({
     era: eraCodec,
     skipped: bool,
     rewards: u128,
-    winners: Vector(accountIdCodec)
+    winners: Vector(accountIdCodec),
+});
+
+export type RaffleAudit = {
+    nbParticipants: number,
+    totalTickets: bigint,
+    snapshotHash: Uint8Array,
+    randomness: Uint8Array,
+}
+
+export const raffleAuditCodec : Codec<RaffleAudit> = Struct({
+    nbParticipants: u32,
+    totalTickets: u128,
+    snapshotHash: hashCodec,
+    randomness: hashCodec,
+});
+
+// the random value of an era is committed in the response of the era RANDOM_COMMITMENT_ERA_OFFSET eras before
+export const RANDOM_COMMITMENT_ERA_OFFSET = 2;
+// the snapshot of the participants of an era is committed in the response of the previous era
+export const SNAPSHOT_COMMITMENT_ERA_OFFSET = 1;
+
+/*
+    #[ink::scale_derive(Encode, Decode)]
+    pub struct RaffleResponseMessageV2 {
+        pub response: RaffleResponseMessage,
+        pub audit: Option<RaffleAudit>,
+        pub next_random_commitment: Option<HashValue>,
+        pub next_snapshot_commitment: Option<HashValue>,
+    }
+ */
+
+export type RaffleResponseMessageV2 = {
+    response: RaffleResponseMessage,
+    audit: RaffleAudit | undefined,
+    nextRandomCommitment: Uint8Array | undefined,
+    nextSnapshotCommitment: Uint8Array | undefined,
+}
+
+export const raffleResponseMessageV2Codec : Codec<RaffleResponseMessageV2> = Struct({
+    response: raffleResponseMessageCodec,
+    audit: Option(raffleAuditCodec),
+    nextRandomCommitment: Option(hashCodec),
+    nextSnapshotCommitment: Option(hashCodec),
+});
+
+/*
+    #[ink::scale_derive(Encode, Decode)]
+    pub enum RaffleResponse {
+        V1(Vec<RaffleResponseMessageV1>),
+        V2 { pool_id: Option<PoolId>, responses: Vec<RaffleResponseMessageV2> },
+    }
+ */
+export const raffleResponseCodec = Enum({
+    V1: Vector(raffleResponseMessageV1Codec),
+    V2: Struct({
+        poolId: Option(u32),
+        responses: Vector(raffleResponseMessageV2Codec),
+    }),
+});
+
+export type RaffleResponse = CodecType<typeof raffleResponseCodec>;
+
+export type DomainSeparator = {
+    chainId: Uint8Array,
+    contract: AccountId,
+}
+
+export const domainSeparatorCodec : Codec<DomainSeparator> = Struct({
+    chainId: hashCodec,
+    contract: accountIdCodec,
+});
+
+// response bound to a deployment so that it cannot be replayed on another one
+export const boundRaffleResponseCodec = Struct({
+    domain: domainSeparatorCodec,
+    response: raffleResponseCodec,
 });
 
+// prefix of the actions sent in the envelope, the messages without envelope start with the era
+export const RESPONSE_ENVELOPE_PREFIX = new Uint8Array([0x4c, 0x4b, 0x59, 0xff]); // b"LKY\xff"
+
+/*
+    #[ink::scale_derive(Encode, Decode)]
+    pub enum ResponseEnvelope {
+        Unbound(RaffleResponse),
+        Bound(BoundRaffleResponse),
+    }
+ */
+export const responseEnvelopeCodec = Enum({
+    Unbound: raffleResponseCodec,
+    Bound: boundRaffleResponseCodec,
+});
+
+export type ResponseEnvelope = CodecType<typeof responseEnvelopeCodec>;
+
+// encode the envelope with its prefix
+export function encodeResponseEnvelope(envelope: ResponseEnvelope): Uint8Array {
+    const encoded = responseEnvelopeCodec.enc(envelope);
+    const action = new Uint8Array(RESPONSE_ENVELOPE_PREFIX.length + encoded.length);
+    action.set(RESPONSE_ENVELOPE_PREFIX);
+    action.set(encoded, RESPONSE_ENVELOPE_PREFIX.length);
+    return action;
+}
 
@@ -0,0 +1,146 @@
+import {blake2b} from "@noble/hashes/blake2b";
+import {u128, u32} from "scale-ts";
+import {type AccountId, type Era} from "./types.ts";
+import {ticketRangeCodec, type PoolId, type TicketProof, type TicketRange, type WinnersProof} from "./wasm_codec.ts";
+
+// prefix of the hash of a leaf, so that a node cannot be presented as a leaf
+const LEAF_PREFIX = 0x00;
+// prefix of the hash of a node
+const NODE_PREFIX = 0x01;
+
+// max number of draws, a draw selecting a participant already drawn or excluded is ignored
+const MAX_DRAWS = 100;
+
+export type Ticket = {
+    account: AccountId,
+    nbTickets: bigint,
+}
+
+function concat(...parts: Uint8Array[]): Uint8Array {
+    const output = new Uint8Array(parts.reduce((length, part) => length + part.length, 0));
+    let offset = 0;
+    for (const part of parts) {
+        output.set(part, offset);
+        offset += part.length;
+    }
+    return output;
+}
+
+function hash(input: Uint8Array): Uint8Array {
+    return blake2b(input, {dkLen: 32});
+}
+
+function compare(left: Uint8Array, right: Uint8Array): number {
+    for (let i = 0; i < left.length; i++) {
+        if (left[i] != right[i]) {
+            return left[i] - right[i];
+        }
+    }
+    return 0;
+}
+
+// hash of a leaf of the snapshot, same as leaf_hash in the contract
+export function leafHash(range: TicketRange): Uint8Array {
+    return hash(concat(new Uint8Array([LEAF_PREFIX]), ticketRangeCodec.enc(range)));
+}
+
+// hash of a node, the children are sorted so that the proof doesn't need the position
+export function nodeHash(left: Uint8Array, right: Uint8Array): Uint8Array {
+    return compare(left, right) <= 0
+        ? hash(concat(new Uint8Array([NODE_PREFIX]), left, right))
+        : hash(concat(new Uint8Array([NODE_PREFIX]), right, left));
+}
+
+// commitment on the random value, sent before the participants of the era are known
+export function randomCommitment(random: Uint8Array): Uint8Array {
+    return hash(random);
+}
+
+// random value used to draw the winners of the era, derived from the secret seed of the worker
+export function deriveRandom(seed: Uint8Array, poolId: PoolId, era: Era): Uint8Array {
+    return hash(concat(seed, u32.enc(poolId), u32.enc(era)));
+}
+
+// ticket drawn for the given era and draw, same as draw_ticket in the contract
+export function drawTicket(random: Uint8Array, era: Era, draw: number, totalTickets: bigint): bigint {
+    const seed = hash(concat(random, u32.enc(era), u32.enc(draw)));
+    return u128.dec(seed.slice(0, 16)) % totalTickets;
+}
+
+// root of the merkle tree and the proof of each leaf
+export function buildMerkleTree(leaves: Uint8Array[]): {root: Uint8Array, proofs: Uint8Array[][]} {
+    const proofs: Uint8Array[][] = leaves.map(() => []);
+    let positions = leaves.map((_, i) => i);
+    let level = leaves;
+    while (level.length > 1) {
+        const nextLevel: Uint8Array[] = [];
+        for (let i = 0; i < level.length; i += 2) {
+            // the last node without sibling is moved to the next level
+            nextLevel.push(i + 1 < level.length ? nodeHash(level[i], level[i + 1]) : level[i]);
+        }
+        positions = positions.map((position, leaf) => {
+            const sibling = position ^ 1;
+            if (sibling < level.length) {
+                proofs[leaf].push(level[sibling]);
+            }
+            return position >> 1;
+        });
+        level = nextLevel;
+    }
+    return {root: level[0], proofs};
+}
+
+// snapshot of the participants: the ticket ranges sorted by account, the root of the merkle tree and the proof of each range.
+// The snapshot contains all the participants of the era, so that it can be committed before the winners of the previous era are known
+export function buildSnapshot(participants: Ticket[]): {ranges: TicketRange[], totalTickets: bigint, root: Uint8Array, proofs: Uint8Array[][]} {
+
+    const ranges: TicketRange[] = [];
+    let totalTickets = BigInt(0);
+    const sorted = participants
+        .filter((participant) => participant.nbTickets > 0)
+        .sort((left, right) => compare(left.account, right.account));
+    for (const participant of sorted) {
+        const start = totalTickets;
+        totalTickets += participant.nbTickets;
+        ranges.push({account: participant.account, start, end: totalTickets});
+    }
+
+    if (totalTickets == BigInt(0)) {
+        throw new Error("NoMoreParticipant");
+    }
+
+    const {root, proofs} = buildMerkleTree(ranges.map(leafHash));
+    return {ranges, totalTickets, root, proofs};
+}
+
+// draw the winners among the participants with the random value and build the proof.
+// A draw selecting a participant already drawn or excluded is ignored
+export function drawWinners(
+    random: Uint8Array,
+    era: Era,
+    participants: Ticket[],
+    nbWinners: number,
+    excluded: AccountId[],
+): {winners: AccountId[], winnersProof: WinnersProof} {
+
+    const {ranges, totalTickets, root, proofs} = buildSnapshot(participants);
+    const isExcluded = (account: AccountId) => excluded.some((e) => compare(e, account) == 0);
+
+    const winners: AccountId[] = [];
+    const draws: TicketProof[] = [];
+    const maxWinners = Math.min(nbWinners, ranges.filter((range) => !isExcluded(range.account)).length);
+    for (let draw = 0; winners.length < maxWinners && draw < MAX_DRAWS; draw++) {
+        const ticket = drawTicket(random, era, draw, totalTickets);
+        const index = ranges.findIndex((range) => range.start <= ticket && ticket < range.end);
+        draws.push({range: ranges[index], proof: proofs[index]});
+        const account = ranges[index].account;
+        if (!isExcluded(account) && !winners.some((winner) => compare(winner, account) == 0)) {
+            winners.push(account);
+        }
+    }
+
+    return {
+        winners,
+        winnersProof: {snapshotRoot: root, totalTickets, random, draws},
+    };
+}
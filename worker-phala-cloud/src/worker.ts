@@ -1,6 +1,5 @@
 import {type ContractConfig, type DappStakingProxyConfig, type WorkerInfo,} from './types';
 import {Indexer} from "./indexer.ts";
-import {Vrf} from "@guigou/util-crypto";
 import {DappStakingProxy} from "./dapp_staking.ts";
 import {RaffleConsumerContract} from "./lucky_raffle.ts";
 
@@ -8,7 +7,6 @@ export class LuckyWorker {
 
     private readonly dappStakingProxy: DappStakingProxy;
     private readonly indexer: Indexer;
-    private readonly vrf: Vrf;
     private readonly raffleConsumerContract: RaffleConsumerContract;
 
     constructor(
@@ -18,8 +16,7 @@ export class LuckyWorker {
         vrfSeed: Uint8Array
     ) {
         this.indexer = new Indexer(graphApi);
-        this.vrf = Vrf.getFromSeed(vrfSeed);
-        this.raffleConsumerContract = new RaffleConsumerContract(raffleContractConfig, this.indexer, this.vrf);
+        this.raffleConsumerContract = new RaffleConsumerContract(raffleContractConfig, this.indexer, vrfSeed);
         this.dappStakingProxy = new DappStakingProxy(dappStakingProxyConfig);
     }
 
@@ -2,7 +2,6 @@ import {expect, test} from "bun:test";
 import type {ContractConfig} from "../src/types.ts";
 import {RaffleConsumerContract} from "../src/lucky_raffle.ts";
 import {Indexer} from "../src/indexer.ts";
-import {Vrf} from "@guigou/util-crypto";
 import {hexToU8a} from "@polkadot/util";
 import {clientContractAddress, pk, rpc} from "./constants.ts";
 
@@ -35,12 +34,10 @@ function getContract() : RaffleConsumerContract {
         throw new Error("Indexer url is missing!");
     }
     const indexer = new Indexer(indexerUrl);
-    const vrf = Vrf.getFromSeed(hexToU8a(config.attestorKey));
-
     return new RaffleConsumerContract(
         config,
         indexer,
-        vrf,
+        hexToU8a(config.attestorKey),
     )
 }
 
@@ -0,0 +1,43 @@
+import {expect, test} from "bun:test";
+import {blake2b} from "@noble/hashes/blake2b";
+import {u8aToHex} from "@polkadot/util";
+import {
+    encodeResponseEnvelope,
+    JACKPOT,
+    LAST_COMMITTED_ERA,
+    LAST_WINNER,
+    NB_WINNERS,
+    NEXT_ERA,
+    PROTOCOL_FEE,
+    RESPONSE_ENVELOPE_PREFIX,
+    responseEnvelopeCodec,
+} from "../src/wasm_codec.ts";
+
+// key used by the contract in the kv store: ink::selector_id!(name) encoded as u32 (little endian)
+function selectorId(name: string): string {
+    const hash = blake2b(new TextEncoder().encode(name), {dkLen: 32});
+    return u8aToHex(hash.slice(0, 4).reverse());
+}
+
+test('kv store keys', () => {
+    expect(NEXT_ERA).toBe(selectorId("NEXT_ERA"));
+    expect(NB_WINNERS).toBe(selectorId("NB_WINNERS"));
+    expect(LAST_WINNER).toBe(selectorId("LAST_WINNER"));
+    expect(JACKPOT).toBe(selectorId("JACKPOT"));
+    expect(PROTOCOL_FEE).toBe(selectorId("PROTOCOL_FEE"));
+    expect(LAST_COMMITTED_ERA).toBe(selectorId("LAST_COMMITTED_ERA"));
+});
+
+test('response envelope', () => {
+    const encoded = encodeResponseEnvelope({
+        tag: 'Unbound',
+        value: {tag: 'V2', value: {poolId: 1, responses: []}},
+    });
+    expect(encoded.slice(0, 4)).toEqual(RESPONSE_ENVELOPE_PREFIX);
+    // the messages without envelope start with the era, the fourth byte of the prefix must not be reachable
+    expect(RESPONSE_ENVELOPE_PREFIX[3]).toBe(0xff);
+    expect(responseEnvelopeCodec.dec(encoded.slice(4))).toEqual({
+        tag: 'Unbound',
+        value: {tag: 'V2', value: {poolId: 1, responses: []}},
+    });
+});
@@ -0,0 +1,53 @@
+import {expect, test} from "bun:test";
+import {buildMerkleTree, buildSnapshot, deriveRandom, drawTicket, drawWinners, leafHash, nodeHash} from "../src/winner_proof.ts";
+
+// same as verify_merkle_proof in the contract
+function computeRoot(leaf: Uint8Array, proof: Uint8Array[]): Uint8Array {
+    return proof.reduce((node, sibling) => nodeHash(node, sibling), leaf);
+}
+
+function account(i: number): Uint8Array {
+    return new Uint8Array(32).fill(i);
+}
+
+test('merkle proofs', () => {
+    const leaves = [1, 2, 3, 4, 5].map((i) => leafHash({account: account(i), start: BigInt(i), end: BigInt(i + 1)}));
+    const {root, proofs} = buildMerkleTree(leaves);
+    for (let i = 0; i < leaves.length; i++) {
+        expect(computeRoot(leaves[i], proofs[i])).toEqual(root);
+    }
+});
+
+test('draw winners', () => {
+    const random = deriveRandom(new Uint8Array(32).fill(7), 0, 13);
+    const participants = [
+        {account: account(1), nbTickets: BigInt(10)},
+        {account: account(2), nbTickets: BigInt(0)},
+        {account: account(3), nbTickets: BigInt(30)},
+    ];
+    const {winners, winnersProof} = drawWinners(random, 13, participants, 2, []);
+
+    expect(winnersProof.totalTickets).toBe(BigInt(40));
+    expect(winners.length).toBe(2);
+    // the participant without ticket is not in the snapshot
+    expect(winners).not.toContainEqual(account(2));
+    winnersProof.draws.forEach((draw, i) => {
+        const ticket = drawTicket(random, 13, i, winnersProof.totalTickets);
+        expect(ticket >= draw.range.start && ticket < draw.range.end).toBeTrue();
+        expect(computeRoot(leafHash(draw.range), draw.proof)).toEqual(winnersProof.snapshotRoot);
+    });
+});
+
+test('draw winners with excluded participants', () => {
+    const random = deriveRandom(new Uint8Array(32).fill(7), 0, 13);
+    const participants = [
+        {account: account(3), nbTickets: BigInt(30)},
+        {account: account(1), nbTickets: BigInt(10)},
+    ];
+    const {winners, winnersProof} = drawWinners(random, 13, participants, 2, [account(3)]);
+
+    // the excluded participant stays in the snapshot, sorted by account, but cannot win
+    expect(winnersProof.totalTickets).toBe(BigInt(40));
+    expect(winnersProof.snapshotRoot).toEqual(buildSnapshot(participants).root);
+    expect(winners).toEqual([account(1)]);
+});